@@ -1,4 +1,5 @@
 extern crate self as vhdl_doc;
 
 pub mod memory_map;
+pub mod output;
 pub mod symbol;
@@ -1,7 +1,13 @@
-use clap::Parser;
+use anyhow::Context;
+use clap::{ArgAction, Parser, ValueEnum};
+use log::{LevelFilter, info};
+use std::io::Write;
 use std::{env, fs, path::PathBuf};
-use vhdl_doc::memory_map::schema::get_memory_map_schema;
-use vhdl_doc::symbol::symbol::make_symbol;
+use vhdl_doc::memory_map::loader::{InputFormat, STDIN_PATH, load_memory_maps};
+use vhdl_doc::memory_map::schema::{JsonStyle, MemoryMap, get_memory_map_schema_styled};
+use vhdl_doc::memory_map::template::init_memory_map;
+use vhdl_doc::output::{stream_if_changed, write_if_changed};
+use vhdl_doc::symbol::symbol::{make_symbol, make_symbol_svg, parse_color};
 
 fn default_path(p: &str) -> PathBuf {
     let mut path = env::current_dir().unwrap();
@@ -9,18 +15,176 @@ fn default_path(p: &str) -> PathBuf {
     path
 }
 
+/// Output format rendered from the memory map
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Format {
+    Json,
+    Toml,
+    Markdown,
+    Vhdl,
+    CHeader,
+    Ipxact,
+    Csv,
+    Html,
+}
+
+impl Format {
+    /// Name of the output file for a source whose file name without extension is `stem`
+    fn file_name(self, stem: &str) -> String {
+        match self {
+            Format::Json => format!("{stem}.json"),
+            Format::Toml => format!("{stem}.toml"),
+            Format::Markdown => format!("{stem}.md"),
+            Format::Vhdl => format!("{stem}_pkg.vhd"),
+            Format::CHeader => format!("{stem}.h"),
+            Format::Ipxact => format!("{stem}.xml"),
+            Format::Csv => format!("{stem}.csv"),
+            Format::Html => format!("{stem}.html"),
+        }
+    }
+
+    /// Streams the elaborated memory map to `out`, laying out JSON according to `json_style`
+    fn render<W: Write>(
+        self,
+        memory_map: &MemoryMap,
+        stem: &str,
+        json_style: JsonStyle,
+        out: &mut W,
+    ) -> Result<(), anyhow::Error> {
+        match self {
+            Format::Json => memory_map.write_json_styled(json_style, out)?,
+            Format::Toml => out.write_all(toml::to_string_pretty(memory_map)?.as_bytes())?,
+            Format::Markdown => memory_map.write_markdown(out)?,
+            Format::Vhdl => memory_map.write_vhdl_package(&format!("{stem}_pkg"), out)?,
+            Format::CHeader => {
+                memory_map.write_c_header(&format!("{}_H", stem.to_uppercase()), out)?
+            }
+            Format::Ipxact => memory_map.write_ipxact(stem, out)?,
+            Format::Csv => memory_map.write_csv(out)?,
+            Format::Html => memory_map.write_html(stem, out)?,
+        }
+        Ok(())
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    #[arg(short, long, default_value = default_path(".").into_os_string())]
-    source_path: PathBuf,
+    /// Memory map to document, as a .toml, .json or .yaml file, or - to read from stdin.
+    /// Several files, or a directory of them, are merged under one shared protocol.
+    #[arg(short, long, num_args = 1.., default_value = default_path("memory_map.toml").into_os_string())]
+    source_path: Vec<PathBuf>,
     #[arg(short, long, default_value = default_path("doc").into_os_string())]
     doc_path: PathBuf,
+    /// Format of the source map, overriding detection by file extension
+    #[arg(long, value_enum)]
+    input_format: Option<InputFormat>,
+    /// Output format written into the doc path
+    #[arg(short, long, value_enum, default_value_t = Format::Json)]
+    format: Format,
+    /// Edge length of the generated symbol image in pixels
+    #[arg(long, default_value_t = 32)]
+    symbol_size: u32,
+    /// Color of the generated symbol as a #RRGGBB hex string
+    #[arg(long, value_parser = parse_color, default_value = "#FF0000")]
+    symbol_color: [u8; 3],
+    /// Increase logging verbosity; -v logs progress and -vv logs each field's address
+    #[arg(short, long, action = ArgAction::Count)]
+    verbose: u8,
+    /// Write a commented template memory map to the given file and exit
+    #[arg(long, value_name = "FILE")]
+    init: Option<PathBuf>,
+    /// Allow --init to overwrite an existing file
+    #[arg(long, requires = "init")]
+    force: bool,
+    /// Elaborate the source map and report any error without writing files
+    #[arg(long)]
+    check: bool,
+    /// Check the source map against the memory map schema before loading it
+    #[arg(long)]
+    validate: bool,
+    /// Fail unless the top-level field of the source map is a set
+    #[arg(long)]
+    strict: bool,
+    /// Write the memory map JSON schema to the given file and exit
+    #[arg(long, value_name = "FILE")]
+    emit_schema: Option<PathBuf>,
+    /// Number of spaces each level of generated JSON is indented by
+    #[arg(long, value_name = "N", default_value_t = 4)]
+    indent: usize,
+    /// Write generated JSON without any whitespace
+    #[arg(long, conflicts_with = "indent")]
+    compact: bool,
+}
+
+impl Args {
+    /// Layout of generated JSON selected by --indent and --compact
+    fn json_style(&self) -> JsonStyle {
+        if self.compact {
+            JsonStyle::Compact
+        } else {
+            JsonStyle::Indent(self.indent)
+        }
+    }
 }
 
-fn main() {
+fn main() -> Result<(), anyhow::Error> {
     let args = Args::parse();
-    fs::create_dir_all(args.doc_path.clone()).unwrap();
-    make_symbol(args.doc_path);
-    println!("{}", get_memory_map_schema());
+    let level = match args.verbose {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_default_env()
+        .init();
+    if let Some(init_path) = args.init {
+        return Ok(init_memory_map(&init_path, args.force)?);
+    }
+    if let Some(schema_path) = &args.emit_schema {
+        let schema = get_memory_map_schema_styled(args.json_style());
+        write_if_changed(schema_path, schema.as_bytes())?;
+        return Ok(());
+    }
+    let stem = match args.source_path.as_slice() {
+        [path] if path.as_os_str() != STDIN_PATH => path
+            .file_stem()
+            .map_or("memory_map".into(), |stem| stem.to_string_lossy()),
+        _ => "memory_map".into(),
+    };
+    let sources = args
+        .source_path
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut memory_map =
+        load_memory_maps(&args.source_path, args.input_format, args.validate, &stem)?;
+    memory_map.set_strict(args.strict);
+    #[cfg(feature = "rayon")]
+    let elaborated = memory_map.elaborate_parallel();
+    #[cfg(not(feature = "rayon"))]
+    let elaborated = memory_map.elaborate();
+    elaborated.with_context(|| format!("Failed to elaborate {sources}"))?;
+    info!(
+        "Elaborated {} fields from {sources}",
+        memory_map.field_count()
+    );
+    if args.check {
+        println!(
+            "OK: {sources} elaborated {} fields",
+            memory_map.field_count()
+        );
+        return Ok(());
+    }
+    fs::create_dir_all(args.doc_path.clone())?;
+    stream_if_changed(args.doc_path.join(args.format.file_name(&stem)), |out| {
+        args.format
+            .render(&memory_map, &stem, args.json_style(), out)
+    })?;
+    make_symbol(args.doc_path.clone(), args.symbol_size, args.symbol_color)?;
+    make_symbol_svg(args.doc_path, args.symbol_color)?;
+    Ok(())
 }
@@ -1 +1,3 @@
+pub mod loader;
 pub mod schema;
+pub mod template;
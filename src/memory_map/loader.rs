@@ -0,0 +1,207 @@
+use crate::memory_map::schema::{MemoryMap, get_memory_map_schema};
+use anyhow::{Context, anyhow, bail};
+use clap::ValueEnum;
+use jsonschema::error::{ValidationError, ValidationErrorKind};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Source path that selects standard input instead of a file
+pub const STDIN_PATH: &str = "-";
+
+/// Serialization format of a memory map source
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum InputFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl InputFormat {
+    /// Selects the format matching a file's extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Some(InputFormat::Toml),
+            Some("json") => Some(InputFormat::Json),
+            Some("yaml") | Some("yml") => Some(InputFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Parses a memory map written in this format
+    pub fn parse(self, contents: &str) -> Result<MemoryMap, anyhow::Error> {
+        Ok(match self {
+            InputFormat::Toml => toml::from_str(contents).context("Failed to parse TOML")?,
+            InputFormat::Json => serde_json::from_str(contents).context("Failed to parse JSON")?,
+            InputFormat::Yaml => serde_yaml::from_str(contents).context("Failed to parse YAML")?,
+        })
+    }
+
+    /// Parses a document in this format without interpreting it as a memory map
+    pub fn parse_value(self, contents: &str) -> Result<serde_json::Value, anyhow::Error> {
+        Ok(match self {
+            InputFormat::Toml => toml::from_str(contents).context("Failed to parse TOML")?,
+            InputFormat::Json => serde_json::from_str(contents).context("Failed to parse JSON")?,
+            InputFormat::Yaml => serde_yaml::from_str(contents).context("Failed to parse YAML")?,
+        })
+    }
+
+    /// Parses a memory map written in this format after checking it against the schema from
+    /// [get_memory_map_schema], so that mistakes such as unknown keys are reported with the
+    /// JSON pointer of each violation rather than as a serde error
+    pub fn parse_validated(self, contents: &str) -> Result<MemoryMap, anyhow::Error> {
+        let value = self.parse_value(contents)?;
+        validate_memory_map(&value)?;
+        serde_json::from_value(value).context("Failed to parse memory map")
+    }
+}
+
+/// Checks a parsed document against the memory map schema, listing every violation along with
+/// the JSON pointer of the offending value
+pub fn validate_memory_map(value: &serde_json::Value) -> Result<(), anyhow::Error> {
+    let schema: serde_json::Value =
+        serde_json::from_str(&get_memory_map_schema()).context("Failed to parse schema")?;
+    let validator = jsonschema::validator_for(&schema).context("Failed to compile schema")?;
+    let mut violations = Vec::new();
+    for err in validator.iter_errors(value) {
+        collect_violations(err.to_owned(), &mut violations);
+    }
+    if !violations.is_empty() {
+        bail!(
+            "Memory map does not match the schema:\n{}",
+            violations.join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// Describes a schema violation as its JSON pointer and message. A value matching none of the
+/// alternatives of an `anyOf` or `oneOf` is described by the alternative that got furthest into
+/// the value, which is usually the one the author intended.
+fn collect_violations(err: ValidationError<'static>, violations: &mut Vec<String>) {
+    let depth = |err: &ValidationError| err.instance_path.to_string().matches('/').count();
+    match err.kind {
+        ValidationErrorKind::AnyOf { context } | ValidationErrorKind::OneOfNotValid { context }
+            if !context.is_empty() =>
+        {
+            let closest = context
+                .into_iter()
+                .rev()
+                .max_by_key(|branch| branch.iter().map(depth).max().unwrap_or(0))
+                .unwrap_or_default();
+            for err in closest {
+                collect_violations(err, violations);
+            }
+        }
+        _ => {
+            let pointer = err.instance_path.to_string();
+            let pointer = if pointer.is_empty() { "/" } else { &pointer };
+            violations.push(format!("  {pointer}: {err}"));
+        }
+    }
+}
+
+/// Reads a memory map from a `.toml`, `.json`, `.yaml` or `.yml` file, selecting the format by
+/// extension unless `format` is given. A path of `-` reads from standard input, falling back
+/// to [parse_memory_map] when no format is given. With `validate` the source is first checked
+/// against the memory map schema.
+pub fn load_memory_map(
+    path: &Path,
+    format: Option<InputFormat>,
+    validate: bool,
+) -> Result<MemoryMap, anyhow::Error> {
+    let parse = |format: InputFormat, contents: &str| match validate {
+        true => format.parse_validated(contents),
+        false => format.parse(contents),
+    };
+    if path == Path::new(STDIN_PATH) {
+        let mut contents = String::new();
+        io::stdin()
+            .read_to_string(&mut contents)
+            .context("Failed to read standard input")?;
+        let detected = || {
+            [InputFormat::Toml, InputFormat::Json, InputFormat::Yaml]
+                .into_iter()
+                .find(|format| format.parse_value(&contents).is_ok())
+        };
+        return match format.or_else(|| detected().filter(|_| validate)) {
+            Some(format) => parse(format, &contents),
+            None => parse_memory_map(&contents),
+        };
+    }
+    let Some(format) = format.or_else(|| InputFormat::from_path(path)) else {
+        bail!(
+            "Unrecognized memory map format for {}; expected a .toml, .json or .yaml file",
+            path.display()
+        );
+    };
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(format, &contents).with_context(|| format!("Failed to load {}", path.display()))
+}
+
+/// Parses a memory map of unknown format, trying TOML first and falling back to JSON, then YAML
+pub fn parse_memory_map(contents: &str) -> Result<MemoryMap, anyhow::Error> {
+    toml::from_str(contents).or_else(|toml_err| {
+        serde_json::from_str(contents).or_else(|json_err| {
+            serde_yaml::from_str(contents).map_err(|yaml_err| {
+                anyhow!(
+                    "Failed to parse memory map as TOML ({toml_err}), as JSON ({json_err}) \
+                     or as YAML ({yaml_err})"
+                )
+            })
+        })
+    })
+}
+
+/// Reads one memory map per source, where a directory contributes each of its memory map
+/// files in name order. Several maps are merged under a top-level set called `name` and must
+/// all declare the same protocol.
+pub fn load_memory_maps(
+    sources: &[PathBuf],
+    format: Option<InputFormat>,
+    validate: bool,
+    name: &str,
+) -> Result<MemoryMap, anyhow::Error> {
+    let mut paths = Vec::new();
+    for source in sources {
+        if source.is_dir() {
+            let mut entries = Vec::new();
+            for entry in fs::read_dir(source)
+                .with_context(|| format!("Failed to read directory {}", source.display()))?
+            {
+                let path = entry?.path();
+                if InputFormat::from_path(&path).is_some() {
+                    entries.push(path);
+                }
+            }
+            if entries.is_empty() {
+                bail!(
+                    "Directory {} contains no memory map files",
+                    source.display()
+                );
+            }
+            entries.sort();
+            paths.append(&mut entries);
+        } else {
+            paths.push(source.clone());
+        }
+    }
+    if let [path] = paths.as_slice() {
+        return load_memory_map(path, format, validate);
+    }
+    let maps = paths
+        .iter()
+        .map(|path| load_memory_map(path, format, validate))
+        .collect::<Result<Vec<_>, _>>()?;
+    MemoryMap::merge(name, maps).with_context(|| {
+        format!(
+            "Failed to merge {}",
+            paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    })
+}
@@ -1,98 +1,543 @@
-use schemars::schema_for;
-use schemars::JsonSchema;
-use serde::de::Visitor;
+use log::{debug, warn};
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{IgnoredAny, MapAccess, SeqAccess, Unexpected, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::ser::PrettyFormatter;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
+use std::io;
+use std::num::IntErrorKind;
+use thiserror::Error;
 
-fn hex_str_or_unsigned<'de, D>(deserializer: D) -> Result<u64, D::Error>
+mod builder;
+mod c_header;
+mod csv;
+mod flat;
+mod html;
+mod ipxact;
+mod markdown;
+mod report;
+mod svg;
+mod systemrdl;
+mod vhdl;
+
+pub use builder::{FieldBuilder, MemoryMapBuilder};
+pub use flat::FlatField;
+pub use html::html_anchor;
+pub use report::{AddressRange, AddressReport};
+
+/// Parses the digits of `val` in the given radix, ignoring underscores between digits
+fn parse_radix<E>(val: &str, digits: &str, radix: u32, kind: &str) -> Result<u64, E>
 where
-    D: Deserializer<'de>,
+    E: serde::de::Error,
+{
+    let deformat = digits.replace("_", "");
+    u64::from_str_radix(&deformat, radix).map_err(|err| match err.kind() {
+        IntErrorKind::PosOverflow => E::custom(format!(
+            "{kind} value {val} exceeds the maximum representable value 0x{:X}",
+            u64::MAX
+        )),
+        _ => E::custom(format!("failed to parse {kind} string {val}")),
+    })
+}
+
+/// Parses a '0x' or '0X' (hex), '0b' (binary) or '0o' (octal) prefixed string, ignoring
+/// underscores between digits. Returns None if the string carries none of these prefixes.
+fn parse_prefixed_int<E>(val: &str) -> Option<Result<u64, E>>
+where
+    E: serde::de::Error,
 {
-    struct HexVisitor;
+    let (digits, radix, kind) =
+        if let Some(digits) = val.strip_prefix("0x").or_else(|| val.strip_prefix("0X")) {
+            (digits, 16, "hex")
+        } else if let Some(digits) = val.strip_prefix("0b") {
+            (digits, 2, "binary")
+        } else if let Some(digits) = val.strip_prefix("0o") {
+            (digits, 8, "octal")
+        } else {
+            return None;
+        };
+    Some(parse_radix(val, digits, radix, kind))
+}
 
-    impl<'de> Visitor<'de> for HexVisitor {
-        type Value = u64;
+struct HexVisitor {
+    /// Also accept hex strings without a prefix
+    lenient: bool,
+}
+
+impl<'de> Visitor<'de> for HexVisitor {
+    type Value = u64;
 
-        fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-            fmt.write_str("unsigned or string")
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.lenient {
+            fmt.write_str("unsigned, hex string, or '0b' or '0o' prefixed string")
+        } else {
+            fmt.write_str("unsigned or '0x', '0b' or '0o' prefixed string")
         }
+    }
 
-        fn visit_i64<E>(self, val: i64) -> Result<Self::Value, E>
-        where
-            E: serde::de::Error,
-        {
-            Ok(val as u64)
+    fn visit_i64<E>(self, val: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        u64::try_from(val).map_err(|_| E::invalid_value(Unexpected::Signed(val), &self))
+    }
+
+    fn visit_u64<E>(self, val: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(val)
+    }
+
+    fn visit_str<E>(self, val: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        match parse_prefixed_int(val) {
+            Some(parsed) => parsed,
+            None if self.lenient => parse_radix(val, val, 16, "hex"),
+            None => Err(E::custom(format!(
+                "failed to parse {val}; expected a '0x', '0b' or '0o' prefixed string"
+            ))),
         }
+    }
+}
+
+/// Binary multipliers of the suffixes accepted by [size_or_unsigned]
+const SIZE_SUFFIXES: [(char, u64); 3] = [('K', 1 << 10), ('M', 1 << 20), ('G', 1 << 30)];
+
+/// Accepts what [HexVisitor] does, as well as decimal sizes with a K, M or G suffix
+struct SizeVisitor;
+
+impl<'de> Visitor<'de> for SizeVisitor {
+    type Value = u64;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(
+            "unsigned, '0x', '0b' or '0o' prefixed string, or decimal size with a K, M or G suffix",
+        )
+    }
+
+    fn visit_i64<E>(self, val: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        u64::try_from(val).map_err(|_| E::invalid_value(Unexpected::Signed(val), &self))
+    }
+
+    fn visit_u64<E>(self, val: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        HexVisitor { lenient: false }.visit_u64(val)
+    }
 
-        fn visit_u64<E>(self, val: u64) -> Result<Self::Value, E>
-        where
-            E: serde::de::Error,
+    fn visit_str<E>(self, val: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let Some((digits, multiplier)) = SIZE_SUFFIXES
+            .iter()
+            .find_map(|&(suffix, multiplier)| Some((val.strip_suffix(suffix)?, multiplier)))
+        else {
+            return HexVisitor { lenient: false }.visit_str(val);
+        };
+        // Prefixed digits such as "0x1K" are rejected rather than read as a multiple of hex
+        if !digits.starts_with(|c: char| c.is_ascii_digit())
+            || !digits.chars().all(|c| c.is_ascii_digit() || c == '_')
         {
-            Ok(val)
+            return Err(E::custom(format!(
+                "failed to parse size {val}; a K, M or G suffix must follow decimal digits"
+            )));
         }
+        parse_radix(val, digits, 10, "size")?
+            .checked_mul(multiplier)
+            .ok_or_else(|| {
+                E::custom(format!(
+                    "size value {val} exceeds the maximum representable value 0x{:X}",
+                    u64::MAX
+                ))
+            })
+    }
+}
 
-        fn visit_str<E>(self, val: &str) -> Result<Self::Value, E>
-        where
-            E: serde::de::Error,
-        {
-            if let Some(stripped) = val.strip_prefix("0x") {
-                let deformat = stripped.to_string().replace("_", "");
-                match u64::from_str_radix(&deformat, 16) {
-                    Ok(parsed_int) => Ok(parsed_int),
-                    Err(_) => Err(E::custom("failed to parse hex string")),
-                }
-            } else {
-                Err(E::custom("failed to parse hex string"))
-            }
+fn size_or_unsigned<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(SizeVisitor)
+}
+
+/// Lenient variant of the address deserializer which additionally accepts bare hex strings
+/// such as "FF". Opt in with `#[serde(deserialize_with = "lenient_hex_or_unsigned")]`.
+pub fn lenient_hex_or_unsigned<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(HexVisitor { lenient: true })
+}
+
+/// Address written for a field
+#[derive(Clone, Debug, PartialEq)]
+enum Address {
+    /// Absolute byte address
+    Absolute(u64),
+    /// Absolute byte address written as a prefixed string, which serializes as originally
+    /// written
+    Prefixed(u64, String),
+    /// Bytes back from the end of the enclosing set, written as a negative address
+    FromEnd(u64),
+}
+
+impl Serialize for Address {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Address::Absolute(address) => serializer.serialize_u64(*address),
+            Address::Prefixed(_, text) => serializer.serialize_str(text),
+            Address::FromEnd(offset) => serializer.serialize_i64(-(*offset as i64)),
+        }
+    }
+}
+
+/// Accepts the unsigned or prefixed forms of [HexVisitor], or a negative integer or negated
+/// prefixed string for an address counted back from the end of the enclosing set
+struct AddressVisitor;
+
+impl AddressVisitor {
+    fn from_end<E>(offset: u64) -> Result<Address, E>
+    where
+        E: serde::de::Error,
+    {
+        match offset {
+            0 => Err(E::custom(
+                "an address from the end of a set must be nonzero",
+            )),
+            offset if offset > i64::MAX as u64 => Err(E::custom(format!(
+                "address -{offset} from the end of a set is out of range"
+            ))),
+            offset => Ok(Address::FromEnd(offset)),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for AddressVisitor {
+    type Value = Address;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("integer or optionally negated '0x', '0b' or '0o' prefixed string")
+    }
+
+    fn visit_i64<E>(self, val: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        if val < 0 {
+            AddressVisitor::from_end(val.unsigned_abs())
+        } else {
+            Ok(Address::Absolute(val as u64))
         }
     }
 
-    deserializer.deserialize_any(HexVisitor)
+    fn visit_u64<E>(self, val: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Address::Absolute(val))
+    }
+
+    fn visit_str<E>(self, val: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let hex = HexVisitor { lenient: false };
+        match val.strip_prefix('-') {
+            Some(digits) => AddressVisitor::from_end(hex.visit_str(digits)?),
+            None => Ok(Address::Prefixed(hex.visit_str(val)?, val.to_string())),
+        }
+    }
 }
 
 fn maybe_hex_str_or_unsigned<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    Ok(Some(hex_str_or_unsigned(deserializer)?))
+    Ok(Some(
+        deserializer.deserialize_any(HexVisitor { lenient: false })?,
+    ))
 }
 
-#[derive(Deserialize, Serialize, JsonSchema)]
-#[serde(rename_all = "camelCase")]
+fn maybe_address<'de, D>(deserializer: D) -> Result<Option<Address>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Some(deserializer.deserialize_any(AddressVisitor)?))
+}
+
+/// Regular expression matching the strings accepted by [parse_prefixed_int], one alternative
+/// per radix
+const PREFIXED_INT_PATTERN: &str = "0[xX][0-9A-Fa-f_]+|0b[01_]+|0o[0-7_]+";
+
+/// Schema of the values accepted by [HexVisitor] without leniency
+fn hex_str_or_unsigned_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "anyOf": [
+            { "type": "integer", "minimum": 0 },
+            { "type": "string", "pattern": format!("^({PREFIXED_INT_PATTERN})$") }
+        ]
+    })
+}
+
+/// Schema of the values accepted by [size_or_unsigned]
+fn size_or_unsigned_schema(generator: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "anyOf": [
+            hex_str_or_unsigned_schema(generator),
+            { "type": "string", "pattern": "^[0-9][0-9_]*[KMG]$" }
+        ]
+    })
+}
+
+/// Schema of the values accepted by [maybe_hex_str_or_unsigned]
+fn maybe_hex_str_or_unsigned_schema(generator: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "anyOf": [
+            hex_str_or_unsigned_schema(generator),
+            { "type": "null" }
+        ]
+    })
+}
+
+/// Schema of the values accepted by [maybe_address]
+fn maybe_address_schema(generator: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "anyOf": [
+            hex_str_or_unsigned_schema(generator),
+            { "type": "integer", "maximum": -1 },
+            { "type": "string", "pattern": format!("^-({PREFIXED_INT_PATTERN})$") },
+            { "type": "null" }
+        ]
+    })
+}
+
+/// A key of an enum map, which is an index when the map is keyed by index and a YAML source
+/// writes it as a bare integer
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EnumKey {
+    Index(u64),
+    Name(String),
+}
+
+/// A value of an enum map: the index of a name or the name of an index
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EnumEntry {
+    Index(u64),
+    Name(String),
+}
+
+/// Parses an index key of an enum map written as a decimal or prefixed integer
+fn enum_index<E>(key: EnumKey) -> Result<u64, E>
+where
+    E: serde::de::Error,
+{
+    match key {
+        EnumKey::Index(index) => Ok(index),
+        EnumKey::Name(key) => match parse_prefixed_int(&key) {
+            Some(parsed) => parsed,
+            None => key
+                .parse()
+                .map_err(|_| E::custom(format!("enum map key {key} is not an index"))),
+        },
+    }
+}
+
+struct EnumMapVisitor;
+
+impl<'de> Visitor<'de> for EnumMapVisitor {
+    type Value = BTreeMap<String, u64>;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("a map of variant names to indices or of indices to variant names")
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Vec::new();
+        while let Some(entry) = access.next_entry::<EnumKey, EnumEntry>()? {
+            entries.push(entry);
+        }
+        let by_name = entries
+            .iter()
+            .all(|(_, entry)| matches!(entry, EnumEntry::Index(_)));
+        let mut map = BTreeMap::new();
+        let mut indices = HashSet::new();
+        for (key, entry) in entries {
+            match (key, entry) {
+                (EnumKey::Name(name), EnumEntry::Index(index)) if by_name => {
+                    map.insert(name, index);
+                }
+                (EnumKey::Index(key), EnumEntry::Index(_)) if by_name => {
+                    return Err(serde::de::Error::custom(format!(
+                        "enum map key {key} is not a variant name"
+                    )));
+                }
+                (key, EnumEntry::Name(name)) => {
+                    let index = enum_index(key)?;
+                    if !indices.insert(index) {
+                        return Err(serde::de::Error::custom(format!(
+                            "enum map lists index {index} more than once"
+                        )));
+                    }
+                    if map.insert(name.clone(), index).is_some() {
+                        return Err(serde::de::Error::custom(format!(
+                            "enum map gives variant {name} more than one index"
+                        )));
+                    }
+                }
+                _ => {
+                    return Err(serde::de::Error::custom(
+                        "enum map mixes name = index and index = name entries",
+                    ));
+                }
+            }
+        }
+        Ok(map)
+    }
+}
+
+/// Deserializes an enum map keyed by either variant name or index into indices keyed by name
+fn enum_map<'de, D>(deserializer: D) -> Result<BTreeMap<String, u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_map(EnumMapVisitor)
+}
+
+/// Schema of the maps accepted by [enum_map]
+fn enum_map_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": "object",
+        "anyOf": [
+            { "additionalProperties": { "type": "integer", "minimum": 0 } },
+            {
+                "propertyNames": { "pattern": format!("^([0-9]+|{PREFIXED_INT_PATTERN})$") },
+                "additionalProperties": { "type": "string" }
+            }
+        ]
+    })
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Protocol {
     /// An optional name for the protocol
     name: Option<String>,
     /// Maximum address in terms of dataMin.
-    /// Accepts '0x' prefixed hex strings with underscores allowed between digits to enhance readability
-    #[serde(deserialize_with = "hex_str_or_unsigned")]
+    /// Accepts '0x' prefixed hex, '0b' prefixed binary, and '0o' prefixed octal strings with
+    /// underscores allowed between digits to enhance readability, as well as decimal sizes with a
+    /// binary K, M or G suffix such as "64K" for 65536
+    #[serde(deserialize_with = "size_or_unsigned")]
+    #[schemars(schema_with = "size_or_unsigned_schema")]
     address_max: u64,
     /// Minimum addressable data size in bytes
     data_min: u8,
+    /// Boundary in bytes that every field other than a set is aligned to, for buses whose
+    /// registers sit on a wider boundary than dataMin. Packed fields are placed at the next
+    /// multiple and explicit addresses must be multiples. Must be a multiple of dataMin;
+    /// defaults to dataMin.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    register_align: Option<u8>,
+    /// Widest field in bytes that the bus can access in a single transaction. Fields other
+    /// than sets, or the elements of arrays, that are wider fail elaboration. Unlimited when
+    /// absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data_max: Option<u8>,
+    /// Byte order of fields wider than one byte. Defaults to little endian.
+    #[serde(default)]
+    endianness: Endianness,
+    /// Address the map is relocated to. Added to every explicit address and to the address
+    /// packing starts from, so that the elaborated map, whose addresses are checked against
+    /// addressMax, sits at this base. Must be a multiple of registerAlign, or of dataMin when
+    /// it is not set. Defaults to 0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "maybe_hex_str_or_unsigned")]
+    #[schemars(schema_with = "maybe_hex_str_or_unsigned_schema")]
+    base_address: Option<u64>,
 }
 
-#[derive(Deserialize, Serialize, JsonSchema)]
+#[derive(Clone, Copy, Default, Deserialize, Serialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Endianness {
+    /// The least significant byte is stored at the lowest address
+    #[default]
+    Little,
+    /// The most significant byte is stored at the lowest address
+    Big,
+}
+
+/// One byte of a field's bit layout
+#[derive(Debug, PartialEq)]
+pub struct ByteLane {
+    /// Byte offset from the field's address
+    pub offset: u64,
+    /// Most significant bit held in this byte
+    pub high: u64,
+    /// Least significant bit held in this byte
+    pub low: u64,
+}
+
+#[derive(Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(untagged)]
 pub enum BitfieldStyle {
     /// Contiguous array of bit names starting at index 0.
     /// If array length is shorter than the field, the remainging bits are marked as 'Reserved'
     FromZero(Vec<String>),
     /// Discrete key-value pairs of bit names and indices
-    Discrete(HashMap<String, u64>),
+    Discrete(BTreeMap<String, u64>),
 }
 
-#[derive(Deserialize, Serialize, JsonSchema)]
+impl BitfieldStyle {
+    /// Bit names keyed by bit index
+    fn named_bits(&self) -> BTreeMap<u64, &str> {
+        match self {
+            BitfieldStyle::FromZero(names) => names
+                .iter()
+                .enumerate()
+                .map(|(index, name)| (index as u64, name.as_str()))
+                .collect(),
+            BitfieldStyle::Discrete(map) => map
+                .iter()
+                .map(|(name, index)| (*index, name.as_str()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum FieldType {
     /// Group of other types, typically used to describe a contiguous block of registers
     Set,
+    /// Single bit flag representing the vhdl type `std_logic`; occupies Protocol.data_min bytes.
+    Boolean,
     /// String type; value is the length of the string in bytes.
     String(u64),
     /// Enumerated type
     Enum {
         length: u64,
-        map: HashMap<String, u64>,
+        /// Variant indices keyed by name, or equivalently variant names keyed by index
+        #[serde(deserialize_with = "enum_map")]
+        #[schemars(schema_with = "enum_map_schema")]
+        map: BTreeMap<String, u64>,
     },
     /// Bitfield with named indices
     Bitfield { length: u64, bits: BitfieldStyle },
@@ -133,27 +578,329 @@ pub enum FieldType {
     /// ```
     /// and results in the binary fixed point form 000000000000.0000 with a resolution of
     /// 2^{-4}, a maximum value of (2^{16-1} - 1) / (2^4), and a minimum value of
-    /// -(2^{16-1} - 1) / (2^4).
+    /// -(2^{16-1}) / (2^4).
     SFixed { high: i64, low: i64 },
+    /// Repeated block of `count` identical elements of any type other than `set`.
+    /// Each element starts `stride` bytes after the previous one; if no stride is provided the
+    /// element's byte size, rounded up to Protocol.data_min, is used.
+    /// For example:
+    /// ```toml
+    /// type.array = { count = 16, element.unsigned = 32 }
+    /// ```
+    Array {
+        count: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        stride: Option<u64>,
+        element: Box<FieldType>,
+    },
+    /// Deliberately unused address space; value is the length of the region in bytes.
+    /// Always read-only, documented with the range `reserved` and never given a value.
+    Reserved(u64),
 }
 
-#[derive(Deserialize, Serialize, JsonSchema)]
+/// Number of bits from subscript `high` down to `low`, zero when `high` is below `low` and
+/// None when the count does not fit in a u64
+fn fixed_length(high: i64, low: i64) -> Option<u64> {
+    u64::try_from((i128::from(high) - i128::from(low) + 1).max(0)).ok()
+}
+
+impl FieldType {
+    /// Number of bytes occupied by a single instance of the type, or None for a set, whose
+    /// size depends on its contents, and for an array too large to address
+    fn byte_width(&self, data_min: u64) -> Option<u64> {
+        match self {
+            FieldType::Set => None,
+            FieldType::Boolean => Some(data_min),
+            FieldType::String(length) | FieldType::Reserved(length) => Some(*length),
+            FieldType::Enum { length, .. }
+            | FieldType::Bitfield { length, .. }
+            | FieldType::Unsigned(length)
+            | FieldType::Signed(length) => Some(length.div_ceil(8)),
+            FieldType::UFixed { high, low } | FieldType::SFixed { high, low } => {
+                Some(fixed_length(*high, *low)?.div_ceil(8))
+            }
+            FieldType::Array {
+                count,
+                stride,
+                element,
+            } => {
+                let stride = match stride {
+                    Some(stride) => *stride,
+                    None => element
+                        .byte_width(data_min)?
+                        .checked_next_multiple_of(data_min)?,
+                };
+                u64::from(*count).checked_mul(stride)
+            }
+        }
+    }
+
+    /// The element type of the innermost of nested arrays, or the type itself otherwise
+    fn innermost(&self) -> &FieldType {
+        match self {
+            FieldType::Array { element, .. } => element.innermost(),
+            _ => self,
+        }
+    }
+
+    /// Whether a field of the type carries a unit of measurement: numeric types, arrays of
+    /// them and sets, which pass their unit on to their members
+    fn takes_unit(&self) -> bool {
+        match self {
+            FieldType::Set
+            | FieldType::Unsigned(_)
+            | FieldType::Signed(_)
+            | FieldType::UFixed { .. }
+            | FieldType::SFixed { .. } => true,
+            FieldType::Array { element, .. } => {
+                !matches!(**element, FieldType::Set) && element.takes_unit()
+            }
+            _ => false,
+        }
+    }
+
+    /// Number of bytes accessed at once when reading or writing the type: the element width
+    /// for arrays and None for sets and reserved space, which is never accessed
+    fn access_width(&self, data_min: u64) -> Option<u64> {
+        match self {
+            FieldType::Array { element, .. } => element.access_width(data_min),
+            FieldType::Reserved(_) => None,
+            _ => self.byte_width(data_min),
+        }
+    }
+
+    /// Whether two enums or two bitfields describe the same variants or bits, regardless of
+    /// the fields they belong to. Always false for other types.
+    fn same_layout(&self, other: &FieldType) -> bool {
+        match (self, other) {
+            (
+                FieldType::Enum { length, map },
+                FieldType::Enum {
+                    length: other_length,
+                    map: other_map,
+                },
+            ) => length == other_length && map == other_map,
+            (
+                FieldType::Bitfield { length, bits },
+                FieldType::Bitfield {
+                    length: other_length,
+                    bits: other_bits,
+                },
+            ) => length == other_length && bits.named_bits() == other_bits.named_bits(),
+            _ => false,
+        }
+    }
+
+    /// Number of bits in the value of types with a bit layout, or None for sets, strings,
+    /// arrays and reserved space
+    fn bit_length(&self) -> Option<u64> {
+        match self {
+            FieldType::Boolean => Some(1),
+            FieldType::Enum { length, .. }
+            | FieldType::Bitfield { length, .. }
+            | FieldType::Unsigned(length)
+            | FieldType::Signed(length) => Some(*length),
+            FieldType::UFixed { high, low } | FieldType::SFixed { high, low } => {
+                fixed_length(*high, *low)
+            }
+            FieldType::Set
+            | FieldType::String(_)
+            | FieldType::Array { .. }
+            | FieldType::Reserved(_) => None,
+        }
+    }
+}
+
+/// Renders the type in the notation used by generated documentation, e.g. `unsigned(16)`
+impl fmt::Display for FieldType {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldType::Set => fmt.write_str("set"),
+            FieldType::Boolean => fmt.write_str("boolean"),
+            FieldType::String(length) => write!(fmt, "string({length})"),
+            FieldType::Enum { length, .. } => write!(fmt, "enum({length})"),
+            FieldType::Bitfield { length, .. } => write!(fmt, "bitfield({length})"),
+            FieldType::Unsigned(length) => write!(fmt, "unsigned({length})"),
+            FieldType::Signed(length) => write!(fmt, "signed({length})"),
+            FieldType::UFixed { high, low } => write!(fmt, "ufixed({high} downto {low})"),
+            FieldType::SFixed { high, low } => write!(fmt, "sfixed({high} downto {low})"),
+            FieldType::Array { count, element, .. } => write!(fmt, "array({count} x {element})"),
+            FieldType::Reserved(length) => write!(fmt, "reserved({length})"),
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
 #[serde(untagged)]
 pub enum OneOrMoreField {
     One(Box<Field>),
     More(Vec<Field>),
 }
 
-#[derive(Deserialize, Serialize, JsonSchema)]
+/// Selects the variant by the shape of the input rather than trying each in turn like an
+/// untagged enum, so that errors within a field, such as an unknown key, are reported as is
+impl<'de> Deserialize<'de> for OneOrMoreField {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct OneOrMoreVisitor;
+
+        impl<'de> Visitor<'de> for OneOrMoreVisitor {
+            type Value = OneOrMoreField;
+
+            fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt.write_str("a field or an array of fields")
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let field = Field::deserialize(MapAccessDeserializer::new(map))?;
+                Ok(OneOrMoreField::One(Box::new(field)))
+            }
+
+            fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                Ok(OneOrMoreField::More(Vec::deserialize(
+                    SeqAccessDeserializer::new(seq),
+                )?))
+            }
+        }
+
+        deserializer.deserialize_any(OneOrMoreVisitor)
+    }
+}
+
+/// A field value.
+/// Accepts '0x' prefixed hex, '0b' prefixed binary, and '0o' prefixed octal strings, optionally
+/// preceded by '-', as integers so that the full 64 bit range can be expressed, with
+/// underscores allowed between digits to enhance readability
+#[derive(Clone, Serialize, JsonSchema)]
 #[serde(untagged)]
 pub enum Value {
     String(String),
     Unsigned(u64),
     Signed(i64),
     Float(f64),
+    Bool(bool),
+    /// An integer written as a prefixed string, which serializes as originally written
+    #[serde(serialize_with = "serialize_prefixed")]
+    #[schemars(with = "String")]
+    Prefixed(i128, String),
 }
 
-#[derive(Deserialize, Serialize, JsonSchema)]
+/// Serializes a [Value::Prefixed] as its original string
+fn serialize_prefixed<S>(_: &i128, literal: &str, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(literal)
+}
+
+impl Value {
+    /// Numeric interpretation of the value, if it has one
+    fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Unsigned(value) => Some(value as f64),
+            Value::Signed(value) => Some(value as f64),
+            Value::Float(value) => Some(value),
+            Value::Prefixed(value, _) => Some(value as f64),
+            Value::String(_) | Value::Bool(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(value) => fmt.write_str(value),
+            Value::Unsigned(value) => write!(fmt, "{value}"),
+            Value::Signed(value) => write!(fmt, "{value}"),
+            Value::Float(value) => write!(fmt, "{value}"),
+            Value::Bool(value) => write!(fmt, "{value}"),
+            Value::Prefixed(_, literal) => fmt.write_str(literal),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ValueVisitor;
+
+        impl Visitor<'_> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt.write_str("string, integer, float, boolean, or prefixed integer string")
+            }
+
+            fn visit_bool<E>(self, val: bool) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(Value::Bool(val))
+            }
+
+            fn visit_i64<E>(self, val: i64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                if val < 0 {
+                    Ok(Value::Signed(val))
+                } else {
+                    Ok(Value::Unsigned(val as u64))
+                }
+            }
+
+            fn visit_u64<E>(self, val: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(Value::Unsigned(val))
+            }
+
+            fn visit_f64<E>(self, val: f64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(Value::Float(val))
+            }
+
+            fn visit_str<E>(self, val: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                let (negative, unsigned) = match val.strip_prefix('-') {
+                    Some(unsigned) => (true, unsigned),
+                    None => (false, val),
+                };
+                let Some(parsed) = parse_prefixed_int::<E>(unsigned) else {
+                    return Ok(Value::String(val.to_string()));
+                };
+                let parsed = i128::from(parsed?);
+                let value = if negative { -parsed } else { parsed };
+                if value < i128::from(i64::MIN) {
+                    return Err(E::custom(format!(
+                        "value {val} is below the minimum representable value {}",
+                        i64::MIN
+                    )));
+                }
+                Ok(Value::Prefixed(value, val.to_string()))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
 pub enum Access {
     /// Read-only access is permitted
     #[serde(rename = "r")]
@@ -164,18 +911,54 @@ pub enum Access {
     /// Both read and write access is permitted
     #[serde(rename = "rw")]
     ReadWrite,
+    /// Readable, and writing 1 to a bit clears it while writing 0 leaves it unchanged
+    #[serde(rename = "w1c")]
+    W1C,
+    /// Writes trigger a single-cycle pulse rather than storing a value; reads return zero
+    #[serde(rename = "strobe")]
+    Strobe,
+}
+
+impl Access {
+    /// Note on write side effects for generated code comments, if the access has any
+    fn side_effect(&self) -> Option<&'static str> {
+        match self {
+            Access::W1C => Some("write 1 to clear"),
+            Access::Strobe => Some("write strobe"),
+            Access::Read | Access::Write | Access::ReadWrite => None,
+        }
+    }
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Access::Read => fmt.write_str("r"),
+            Access::Write => fmt.write_str("w"),
+            Access::ReadWrite => fmt.write_str("rw"),
+            Access::W1C => fmt.write_str("w1c"),
+            Access::Strobe => fmt.write_str("strobe"),
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct Field {
     name: String,
+    /// Free-text description of the field for generated documentation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
     /// Memory address. If no address is provided, the renderer will assume the field
-    /// is packed directly following the previously defined address. If padding is desired to
-    /// ensure allignment to Protocol.data_min, and the data type is smaller than data_min, it is
-    /// required to explicitly specify the address.
+    /// is packed following the previously defined address, rounded up to the next multiple of
+    /// Protocol.data_min. An explicit address must be aligned to Protocol.data_min.
+    /// A negative address places the field that many bytes back from the end of its set,
+    /// which is extended past its other members to hold the furthest such field. An address
+    /// written as a prefixed string is serialized as written.
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(default, deserialize_with = "maybe_hex_str_or_unsigned")]
-    address: Option<u64>,
+    #[serde(default, deserialize_with = "maybe_address")]
+    #[schemars(schema_with = "maybe_address_schema")]
+    address: Option<Address>,
     /// Register access permission.
     /// If no access permission is specified, the renterer will assume the field inherits
     /// access from its parent context.
@@ -188,10 +971,25 @@ pub struct Field {
     /// FieldType::Set.
     #[serde(skip_serializing_if = "Option::is_none")]
     contains: Option<OneOrMoreField>,
+    /// Packs consecutive members narrower than a byte that have no explicit address into the
+    /// same Protocol.data_min unit, each starting at the bit following the previous one. A
+    /// member that does not fit in the rest of the unit starts the next one. Used only when
+    /// Field.FieldType is FieldType::Set. Defaults to false.
+    #[serde(
+        rename = "packBits",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pack_bits: bool,
     /// The default value of the field. Ignored for FieldType::Set
     #[serde(skip_serializing_if = "Option::is_none")]
     value: Option<Value>,
+    /// The value the field takes on reset, when it differs from its documented default value.
+    /// Defaults to Field.value. Ignored for FieldType::Set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reset: Option<Value>,
     /// The unit of measurement of a numeric type. Ignored for other types.
+    /// If no unit is specified, a numeric field inherits the unit of its parent set.
     #[serde(skip_serializing_if = "Option::is_none")]
     unit: Option<String>,
     /// The minimum allowed value of a numeric type. Ignored for other types.
@@ -200,20 +998,1700 @@ pub struct Field {
     /// The maximum allowed value of a numeric type. Ignored for other types.
     #[serde(skip_serializing_if = "Option::is_none")]
     max: Option<f64>,
+    /// Populated by the renderer. For numeric types, the minimum and maximum values of the
+    /// field. For sets, the minimum and maximum addresses contained within the set. For other
+    /// types, a description of possible values. Accepted on input so that elaborated output
+    /// can be read back, but always replaced during elaboration.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    range: String,
+    /// Populated by the renderer. Bytes from the enclosing set's address to this field's
+    /// address. Absent for the top-level field and for fields placed before their set's
+    /// address. Accepted on input like Field.range, but always replaced during elaboration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    offset: Option<u64>,
+    /// Populated by the renderer. Bit of the field's address at which a member packed by its
+    /// set's Field.packBits starts. Accepted on input like Field.range, but always replaced
+    /// during elaboration.
+    #[serde(rename = "bitOffset", default, skip_serializing_if = "Option::is_none")]
+    bit_offset: Option<u64>,
+    /// Populated by the renderer. Dotted names of the enclosing sets below the top-level set
+    /// followed by this field's name, used to locate the field in error messages.
+    #[serde(skip)]
+    path: String,
+    /// Populated by the renderer. The address given on input, recorded by the first
+    /// elaboration so that later ones start from it rather than from the assigned address.
+    #[serde(skip)]
+    declared_address: Option<Option<Address>>,
 }
 
-#[derive(Deserialize, Serialize, JsonSchema)]
-pub struct MemoryMap {
-    protocol: Protocol,
-    #[serde(flatten)]
-    field: Field,
+/// Renders the largest value representable by `bits` unsigned bits
+fn unsigned_max(bits: u64) -> String {
+    if bits < 128 {
+        ((1u128 << bits) - 1).to_string()
+    } else {
+        format!("2^{bits} - 1")
+    }
 }
 
-pub fn get_memory_map_schema() -> String {
-    let schema = schema_for!(MemoryMap);
-    let formatter = PrettyFormatter::with_indent(b"    ");
-    let mut buf = Vec::new();
-    let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
-    serde::Serialize::serialize(&schema, &mut ser).expect("Failed to serialize schema");
-    String::from_utf8(buf).expect("Failed to convert serial buffer to string")
+/// Whether a numeric value lies within the integers `min..=max`. Values that are not numbers
+/// are left to the checks of their type.
+fn integer_fits(value: &Value, min: i128, max: i128) -> bool {
+    let range = min..=max;
+    match *value {
+        Value::Unsigned(value) => range.contains(&i128::from(value)),
+        Value::Signed(value) => range.contains(&i128::from(value)),
+        Value::Prefixed(value, _) => range.contains(&value),
+        Value::Float(value) => value >= min as f64 && value <= max as f64,
+        Value::String(_) | Value::Bool(_) => true,
+    }
+}
+
+/// Renders the smallest and largest values representable by `bits` two's complement bits
+fn signed_min_max(bits: u64) -> (String, String) {
+    if bits < 128 {
+        let half = 1i128 << (bits - 1);
+        (format!("-{half}"), (half - 1).to_string())
+    } else {
+        (format!("-2^{}", bits - 1), format!("2^{} - 1", bits - 1))
+    }
+}
+
+/// Describes a run of reserved bits
+fn reserved_bits(first: u64, last: u64) -> String {
+    if first == last {
+        format!("b{first}=Reserved")
+    } else {
+        format!("b{first}..b{last}=Reserved")
+    }
+}
+
+/// Lists the named bits of a bitfield in index order, collapsing unnamed runs into reserved
+/// ranges, e.g. `b0=ready, b1=error, b2..b7=Reserved`
+fn bitfield_range(length: u64, bits: &BitfieldStyle) -> String {
+    let named = bits.named_bits();
+    let mut parts = Vec::new();
+    let mut next = 0;
+    for (&index, name) in named.range(..length) {
+        if index > next {
+            parts.push(reserved_bits(next, index - 1));
+        }
+        parts.push(format!("b{index}={name}"));
+        next = index + 1;
+    }
+    if next < length {
+        parts.push(reserved_bits(next, length - 1));
+    }
+    parts.join(", ")
+}
+
+/// The variants of an enum as (value, name) pairs in value order
+fn sorted_variants(map: &BTreeMap<String, u64>) -> Vec<(u64, &str)> {
+    let mut variants: Vec<(u64, &str)> = map
+        .iter()
+        .map(|(name, value)| (*value, name.as_str()))
+        .collect();
+    variants.sort();
+    variants
+}
+
+/// Lists the variants of an enum in value order, e.g. `0=idle, 1=run`
+fn enum_range(map: &BTreeMap<String, u64>) -> String {
+    sorted_variants(map)
+        .iter()
+        .map(|(value, name)| format!("{value}={name}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Number of hex digits needed to print any address up to `address_max`
+fn hex_digits(address_max: u64) -> usize {
+    (u64::BITS - address_max.leading_zeros()).div_ceil(4).max(1) as usize
+}
+
+/// Enum and bitfield types already emitted by a code generator, letting structurally identical
+/// types be declared once and shared by every field using them
+#[derive(Default)]
+struct SharedTypes<'a> {
+    declared: Vec<(&'a FieldType, String)>,
+}
+
+impl<'a> SharedTypes<'a> {
+    /// Returns the identifier of an earlier declaration with the same layout, or records
+    /// `identifier` as the declaration of `field_type` and returns None. Only enums and
+    /// bitfields are recorded since no other type can share a declaration.
+    fn shared(&mut self, field_type: &'a FieldType, identifier: &str) -> Option<&str> {
+        if !matches!(
+            field_type,
+            FieldType::Enum { .. } | FieldType::Bitfield { .. }
+        ) {
+            return None;
+        }
+        match self
+            .declared
+            .iter()
+            .position(|(declared, _)| declared.same_layout(field_type))
+        {
+            Some(index) => Some(&self.declared[index].1),
+            None => {
+                self.declared.push((field_type, identifier.to_string()));
+                None
+            }
+        }
+    }
+}
+
+/// Converts a field name into an upper case identifier for generated code, e.g. "A nice group"
+/// becomes `A_NICE_GROUP`
+fn identifier(name: &str) -> String {
+    let mut identifier = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            identifier.push(c.to_ascii_uppercase());
+        } else if !identifier.is_empty() && !identifier.ends_with('_') {
+            identifier.push('_');
+        }
+    }
+    let identifier = identifier.trim_end_matches('_');
+    if identifier.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        identifier.to_string()
+    } else {
+        format!("F_{identifier}")
+    }
+}
+
+impl Field {
+    /// A field of the given type with nothing else specified
+    fn new(name: &str, field_type: FieldType) -> Field {
+        Field {
+            name: name.to_string(),
+            description: None,
+            address: None,
+            access: None,
+            field_type,
+            contains: None,
+            value: None,
+            reset: None,
+            unit: None,
+            min: None,
+            max: None,
+            range: String::new(),
+            pack_bits: false,
+            offset: None,
+            bit_offset: None,
+            path: String::new(),
+            declared_address: None,
+        }
+    }
+
+    /// Name of the field
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Free-text description of the field
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Dotted path of the field below the top-level set, populated by
+    /// [`MemoryMap::elaborate`]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Access permission of the field, given explicitly or inherited during elaboration
+    pub fn access(&self) -> Option<Access> {
+        self.access
+    }
+
+    /// Type of the field
+    pub fn field_type(&self) -> &FieldType {
+        &self.field_type
+    }
+
+    /// Range of the field rendered by [`MemoryMap::elaborate`]; empty before elaboration
+    pub fn range(&self) -> &str {
+        &self.range
+    }
+
+    /// The fields contained by a set, in declaration order
+    pub fn children(&self) -> &[Field] {
+        match &self.contains {
+            Some(OneOrMoreField::One(field)) => std::slice::from_ref(field.as_ref()),
+            Some(OneOrMoreField::More(fields)) => fields,
+            None => &[],
+        }
+    }
+
+    /// The fields contained by a set in declaration order, grouped so that those packed into
+    /// the same Protocol.data_min unit by Field.packBits share a group. Every other field is a
+    /// group of its own.
+    fn member_groups(&self) -> impl Iterator<Item = &[Field]> {
+        self.children().chunk_by(|field, next| {
+            field.bit_offset.is_some()
+                && next.bit_offset.is_some()
+                && field.address() == next.address()
+        })
+    }
+
+    /// Bits occupied by a field packed by its set's Field.packBits, as its bit offset and
+    /// length
+    fn packed_bits(&self) -> Option<(u64, u64)> {
+        Some((self.bit_offset?, self.field_type.bit_length()?))
+    }
+
+    /// Mutable access to the fields contained by a set, in declaration order
+    fn children_mut(&mut self) -> &mut [Field] {
+        match &mut self.contains {
+            Some(OneOrMoreField::One(field)) => std::slice::from_mut(field.as_mut()),
+            Some(OneOrMoreField::More(fields)) => fields,
+            None => &mut [],
+        }
+    }
+
+    /// Absolute address of the field, once assigned by [`MemoryMap::elaborate`] or when
+    /// given explicitly
+    pub fn address(&self) -> Option<u64> {
+        match self.address {
+            Some(Address::Absolute(address) | Address::Prefixed(address, _)) => Some(address),
+            Some(Address::FromEnd(_)) | None => None,
+        }
+    }
+
+    /// Records the address assigned to the field, keeping the written form of an explicit
+    /// address that it matches
+    fn assign_address(&mut self, address: u64) {
+        if self.address() != Some(address) {
+            self.address = Some(Address::Absolute(address));
+        }
+    }
+
+    /// Returns this field and its members to the addresses given on input, so that elaborating
+    /// again reproduces the same layout instead of treating assigned addresses as explicit.
+    /// Explicit absolute addresses are moved up by `base`.
+    fn restore_declared_addresses(&mut self, base: u64) {
+        self.address = self
+            .declared_address
+            .get_or_insert_with(|| self.address.clone())
+            .clone();
+        self.bit_offset = None;
+        if base > 0
+            && let Some(address) = self.address()
+        {
+            // An address pushed past u64::MAX is beyond any addressMax, which reports it
+            self.address = Some(Address::Absolute(address.saturating_add(base)));
+        }
+        for child in self.children_mut() {
+            child.restore_declared_addresses(base);
+        }
+    }
+
+    /// Bytes back from the end of the enclosing set, for a field given a negative address
+    fn offset_from_end(&self) -> Option<u64> {
+        match self.address {
+            Some(Address::FromEnd(offset)) => Some(offset),
+            Some(Address::Absolute(_) | Address::Prefixed(..)) | None => None,
+        }
+    }
+
+    /// Bytes spanned by this field, or from a set's address to the end of its last member.
+    /// Returns 0 when [`Field::end_address`] is unknown, which before elaboration is the case
+    /// unless every field involved was given an explicit address.
+    pub fn size_bytes(&self, protocol: &Protocol) -> u64 {
+        match (self.address(), self.end_address(protocol)) {
+            (Some(address), Some(end)) => end - address,
+            _ => 0,
+        }
+    }
+
+    /// Address following the last byte occupied by this field, or by any field within a set.
+    /// Returns None when the field or a member of the set has no address yet, as before
+    /// elaboration unless one was given explicitly, or when the end lies beyond `u64::MAX`.
+    fn end_address(&self, protocol: &Protocol) -> Option<u64> {
+        let address = self.address()?;
+        match self.field_type.byte_width(u64::from(protocol.data_min)) {
+            Some(bytes) => address.checked_add(bytes),
+            None => self
+                .children()
+                .iter()
+                .map(|child| child.end_address(protocol))
+                .try_fold(address, |end, child_end| Some(end.max(child_end?))),
+        }
+    }
+
+    /// Maps each byte of the field's value, padded to a whole number of Protocol.data_min
+    /// units, to its offset from the field's address according to Protocol.endianness.
+    /// Lanes are listed from the least significant byte. Empty for sets, strings and arrays.
+    pub fn byte_lanes(&self, protocol: &Protocol) -> Vec<ByteLane> {
+        let Some(length) = self.field_type.bit_length() else {
+            return Vec::new();
+        };
+        let lanes = length
+            .max(1)
+            .div_ceil(8)
+            .next_multiple_of(u64::from(protocol.data_min.max(1)));
+        (0..lanes)
+            .map(|lane| ByteLane {
+                offset: match protocol.endianness {
+                    Endianness::Little => lane,
+                    Endianness::Big => lanes - 1 - lane,
+                },
+                high: lane * 8 + 7,
+                low: lane * 8,
+            })
+            .collect()
+    }
+
+    /// Formats the range string, preferring the user supplied min/max over the type limits
+    fn numeric_range(&self, type_min: String, type_max: String) -> String {
+        let min = self.min.map_or(type_min, |min| min.to_string());
+        let max = self.max.map_or(type_max, |max| max.to_string());
+        format!("{min} .. {max}")
+    }
+
+    /// The field's value and reset value, each labeled for error messages
+    fn values(&self) -> impl Iterator<Item = (&'static str, &Value)> {
+        [("value", &self.value), ("reset", &self.reset)]
+            .into_iter()
+            .filter_map(|(label, value)| Some((label, value.as_ref()?)))
+    }
+
+    /// Warns about value declarations that are likely oversights given the field's access
+    fn warn_values(&self, access: Access) {
+        let numeric = matches!(
+            self.field_type,
+            FieldType::Bitfield { .. }
+                | FieldType::Unsigned(_)
+                | FieldType::Signed(_)
+                | FieldType::UFixed { .. }
+                | FieldType::SFixed { .. }
+        );
+        if numeric && access == Access::Read && self.values().next().is_none() {
+            warn!(
+                "Read-only field {} documents no value or reset value",
+                self.path
+            );
+        }
+        if access == Access::Write
+            && let Some((label, _)) = self.values().next()
+        {
+            warn!(
+                "Write-only field {} declares a {label} that can never be read back; remove it \
+                 or change the access",
+                self.path
+            );
+        }
+    }
+
+    /// Value the field takes on reset, falling back to its value
+    pub fn reset_value(&self) -> Option<&Value> {
+        self.reset.as_ref().or(self.value.as_ref())
+    }
+
+    /// Error for a type whose parameters leave the field without a valid layout
+    fn invalid_type(&self, reason: String) -> ElaborationError {
+        ElaborationError::InvalidType {
+            path: self.path.clone(),
+            reason,
+        }
+    }
+
+    /// Validates the user supplied min/max bounds and that the value and reset lie within them
+    fn check_min_max(&self) -> Result<(), ElaborationError> {
+        if let (Some(min), Some(max)) = (self.min, self.max)
+            && min > max
+        {
+            return Err(ElaborationError::InvertedBounds {
+                path: self.path.clone(),
+                min,
+                max,
+            });
+        }
+        for (label, value) in self.values() {
+            let Some(value) = value.as_f64() else {
+                continue;
+            };
+            let bound = match (self.min, self.max) {
+                (Some(min), _) if value < min => Bound::Min(min),
+                (_, Some(max)) if value > max => Bound::Max(max),
+                _ => continue,
+            };
+            return Err(ElaborationError::ValueOutOfRange {
+                path: self.path.clone(),
+                label,
+                value,
+                bound,
+            });
+        }
+        Ok(())
+    }
+
+    /// Validates that the value and reset are representable by the field's type, as decided
+    /// by `fits`
+    fn check_type_range(&self, fits: impl Fn(&Value) -> bool) -> Result<(), ElaborationError> {
+        match self.values().find(|(_, value)| !fits(value)) {
+            Some((label, value)) => Err(ElaborationError::OutsideType {
+                path: self.path.clone(),
+                label,
+                value: value.to_string(),
+                field_type: self.field_type.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Validates that `length` bits can hold every index of the enum
+    fn check_enum_length(
+        &self,
+        length: u64,
+        map: &BTreeMap<String, u64>,
+    ) -> Result<(), ElaborationError> {
+        let Some(&max_index) = map.values().max() else {
+            return Ok(());
+        };
+        let required = u64::from(u64::BITS - max_index.leading_zeros()).max(1);
+        if length < required {
+            return Err(ElaborationError::EnumTooShort {
+                path: self.path.clone(),
+                length,
+                max_index,
+                required,
+            });
+        }
+        Ok(())
+    }
+
+    /// Validates that an enum's value and reset name one of its variants or one of their
+    /// mapped values
+    fn check_enum_value(&self, map: &BTreeMap<String, u64>) -> Result<(), ElaborationError> {
+        for (label, value) in self.values() {
+            let valid = match value {
+                Value::String(name) => map.contains_key(name),
+                Value::Unsigned(value) => map.values().any(|mapped| mapped == value),
+                Value::Prefixed(value, _) => {
+                    map.values().any(|mapped| i128::from(*mapped) == *value)
+                }
+                Value::Signed(_) | Value::Float(_) | Value::Bool(_) => false,
+            };
+            if !valid {
+                let mut variants: Vec<String> = map.keys().cloned().collect();
+                variants.sort();
+                return Err(ElaborationError::UnknownVariant {
+                    path: self.path.clone(),
+                    label,
+                    value: serde_json::to_string(value).unwrap_or_default(),
+                    variants,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the explicit address if aligned to data_min, otherwise the running address
+    /// rounded up to the next data_min boundary. Fields other than sets are further aligned
+    /// to Protocol.register_align when it is set.
+    fn resolve_address(
+        &self,
+        running_address: u64,
+        protocol: &Protocol,
+    ) -> Result<u64, ElaborationError> {
+        let data_min = u64::from(protocol.data_min);
+        let alignment = match (&self.field_type, protocol.register_align) {
+            (FieldType::Set, _) | (_, None) => data_min,
+            (_, Some(register_align)) => u64::from(register_align),
+        };
+        match self.address() {
+            Some(address) if address % data_min != 0 => Err(ElaborationError::Misaligned {
+                path: self.path.clone(),
+                address,
+                data_min,
+            }),
+            Some(address) if address % alignment != 0 => {
+                Err(ElaborationError::MisalignedRegister {
+                    path: self.path.clone(),
+                    address,
+                    register_align: alignment,
+                })
+            }
+            Some(address) => Ok(address),
+            None => running_address
+                .checked_next_multiple_of(alignment)
+                .ok_or_else(|| ElaborationError::AddressOverflow {
+                    path: self.path.clone(),
+                    address: running_address,
+                }),
+        }
+    }
+
+    /// Assigns this field's address and advances the running address past its footprint
+    fn render_address(
+        &mut self,
+        bytes: u64,
+        running_address: &mut u64,
+        protocol: &Protocol,
+    ) -> Result<(), ElaborationError> {
+        let my_address = self.resolve_address(*running_address, protocol)?;
+        self.assign_address(my_address);
+        debug!(
+            "Field {} assigned address 0x{:x} with a width of {} bytes",
+            self.path, my_address, bytes
+        );
+        *running_address =
+            my_address
+                .checked_add(bytes)
+                .ok_or_else(|| ElaborationError::AddressOverflow {
+                    path: self.path.clone(),
+                    address: my_address,
+                })?;
+        if bytes > 0 && *running_address - 1 > protocol.address_max {
+            return Err(ElaborationError::ExceedsAddressMax {
+                path: self.path.clone(),
+                address: my_address,
+                address_max: protocol.address_max,
+            });
+        }
+        Ok(())
+    }
+
+    fn render_field_type_boolean(
+        &mut self,
+        running_address: &mut u64,
+        protocol: &Protocol,
+        errors: &mut Vec<ElaborationError>,
+    ) -> Result<(), ElaborationError> {
+        if let Some((label, _)) = self
+            .values()
+            .find(|(_, value)| !matches!(value, Value::Bool(_)))
+        {
+            errors.push(ElaborationError::TypeMismatch {
+                path: self.path.clone(),
+                label,
+            });
+        }
+        self.range = "false | true".to_string();
+        self.render_address(u64::from(protocol.data_min), running_address, protocol)
+    }
+
+    fn render_field_type_string(
+        &mut self,
+        length: u64,
+        running_address: &mut u64,
+        protocol: &Protocol,
+        errors: &mut Vec<ElaborationError>,
+    ) -> Result<(), ElaborationError> {
+        errors.extend(
+            self.check_type_range(|value| match value {
+                Value::String(text) => text.len() as u64 <= length,
+                _ => true,
+            })
+            .err(),
+        );
+        self.range = format!("{length} ASCII bytes");
+        self.render_address(length, running_address, protocol)
+    }
+
+    fn render_field_type_reserved(
+        &mut self,
+        length: u64,
+        running_address: &mut u64,
+        protocol: &Protocol,
+    ) -> Result<(), ElaborationError> {
+        self.access = Some(Access::Read);
+        self.range = "reserved".to_string();
+        self.render_address(length, running_address, protocol)
+    }
+
+    fn render_field_type_enum(
+        &mut self,
+        length: u64,
+        running_address: &mut u64,
+        protocol: &Protocol,
+        errors: &mut Vec<ElaborationError>,
+    ) -> Result<(), ElaborationError> {
+        if let FieldType::Enum { map, .. } = &self.field_type {
+            if map.is_empty() {
+                return Err(self.invalid_type("is an enum without any variants".to_string()));
+            }
+            errors.extend(self.check_enum_length(length, map).err());
+            errors.extend(self.check_enum_value(map).err());
+            self.range = enum_range(map);
+        }
+        self.render_address(length.div_ceil(8), running_address, protocol)
+    }
+
+    fn render_field_type_bitfield(
+        &mut self,
+        length: u64,
+        running_address: &mut u64,
+        protocol: &Protocol,
+    ) -> Result<(), ElaborationError> {
+        if let FieldType::Bitfield { bits, .. } = &self.field_type {
+            self.range = bitfield_range(length, bits);
+        }
+        self.render_address(length.div_ceil(8), running_address, protocol)
+    }
+
+    fn render_field_type_unsigned(
+        &mut self,
+        length: u64,
+        running_address: &mut u64,
+        protocol: &Protocol,
+        errors: &mut Vec<ElaborationError>,
+    ) -> Result<(), ElaborationError> {
+        errors.extend(self.check_min_max().err());
+        if length == 0 {
+            return Err(self.invalid_type("has an unsigned type with zero length".to_string()));
+        }
+        let max = if length < 127 {
+            (1 << length) - 1
+        } else {
+            i128::MAX
+        };
+        errors.extend(
+            self.check_type_range(|value| integer_fits(value, 0, max))
+                .err(),
+        );
+        self.range = self.numeric_range("0".to_string(), unsigned_max(length));
+        self.render_address(length.div_ceil(8), running_address, protocol)
+    }
+
+    fn render_field_type_signed(
+        &mut self,
+        length: u64,
+        running_address: &mut u64,
+        protocol: &Protocol,
+        errors: &mut Vec<ElaborationError>,
+    ) -> Result<(), ElaborationError> {
+        errors.extend(self.check_min_max().err());
+        if length == 0 {
+            return Err(self.invalid_type("has a signed type with zero length".to_string()));
+        }
+        let (type_min, type_max) = if length < 128 {
+            (-(1 << (length - 1)), (1 << (length - 1)) - 1)
+        } else {
+            (i128::MIN, i128::MAX)
+        };
+        errors.extend(
+            self.check_type_range(|value| integer_fits(value, type_min, type_max))
+                .err(),
+        );
+        let (min, max) = signed_min_max(length);
+        self.range = self.numeric_range(min, max);
+        self.render_address(length.div_ceil(8), running_address, protocol)
+    }
+
+    /// Width in bits of a `kind` fixed point type from subscript `high` down to `low`, along
+    /// with `low` and the width as exponents of two, failing when the subscripts are inverted
+    /// or the width cannot be represented
+    fn fixed_exponents(
+        &self,
+        kind: &str,
+        high: i64,
+        low: i64,
+    ) -> Result<(u64, i32, i32), ElaborationError> {
+        if high < low {
+            return Err(self.invalid_type(format!(
+                "has a {kind} high subscript below its low subscript"
+            )));
+        }
+        let exponents = fixed_length(high, low).and_then(|length| {
+            Some((
+                length,
+                i32::try_from(low).ok()?,
+                i32::try_from(length).ok()?,
+            ))
+        });
+        exponents.ok_or_else(|| {
+            self.invalid_type(format!(
+                "has {kind} subscripts {high} downto {low}, beyond the widths that can be represented"
+            ))
+        })
+    }
+
+    fn render_field_type_ufixed(
+        &mut self,
+        high: i64,
+        low: i64,
+        running_address: &mut u64,
+        protocol: &Protocol,
+        errors: &mut Vec<ElaborationError>,
+    ) -> Result<(), ElaborationError> {
+        errors.extend(self.check_min_max().err());
+        let (length, low, width) = self.fixed_exponents("ufixed", high, low)?;
+        let resolution = 2f64.powi(low);
+        let max = (2f64.powi(width) - 1.0) * resolution;
+        errors.extend(
+            self.check_type_range(|value| {
+                value
+                    .as_f64()
+                    .is_none_or(|value| (0.0..=max).contains(&value))
+            })
+            .err(),
+        );
+        self.range = self.numeric_range(format!("{:?}", 0.0), format!("{max:?}"));
+        self.render_address(length.div_ceil(8), running_address, protocol)
+    }
+
+    fn render_field_type_sfixed(
+        &mut self,
+        high: i64,
+        low: i64,
+        running_address: &mut u64,
+        protocol: &Protocol,
+        errors: &mut Vec<ElaborationError>,
+    ) -> Result<(), ElaborationError> {
+        errors.extend(self.check_min_max().err());
+        let (length, low, width) = self.fixed_exponents("sfixed", high, low)?;
+        let resolution = 2f64.powi(low);
+        let half = 2f64.powi(width - 1);
+        let min = -half * resolution;
+        let max = (half - 1.0) * resolution;
+        errors.extend(
+            self.check_type_range(|value| {
+                value
+                    .as_f64()
+                    .is_none_or(|value| (min..=max).contains(&value))
+            })
+            .err(),
+        );
+        self.range = self.numeric_range(format!("{min:?}"), format!("{max:?}"));
+        self.render_address(length.div_ceil(8), running_address, protocol)
+    }
+
+    /// Checks the element type of an array like a field of that type holding the array's
+    /// value and reset within its bounds. Only the type is checked: the array's own address,
+    /// width and access are checked when it is laid out.
+    fn check_array_element(
+        &self,
+        element: &FieldType,
+        protocol: &Protocol,
+        errors: &mut Vec<ElaborationError>,
+    ) -> Result<(), ElaborationError> {
+        // Named after the array's path, which a top-level field takes as its own
+        let mut scratch = Field {
+            access: Some(Access::ReadWrite),
+            value: self.value.clone(),
+            reset: self.reset.clone(),
+            min: self.min,
+            max: self.max,
+            ..Field::new(&self.path, element.clone())
+        };
+        let unbounded = Protocol {
+            name: None,
+            address_max: u64::MAX,
+            data_min: protocol.data_min,
+            register_align: None,
+            data_max: None,
+            endianness: protocol.endianness,
+            base_address: None,
+        };
+        let inherited = Inherited {
+            access: Access::ReadWrite,
+            unit: None,
+        };
+        scratch.render_recursive(&mut 0, &inherited, &unbounded, None, errors, false)
+    }
+
+    fn render_field_type_array(
+        &mut self,
+        count: u32,
+        stride: Option<u64>,
+        element_bytes: Option<u64>,
+        running_address: &mut u64,
+        protocol: &Protocol,
+    ) -> Result<(), ElaborationError> {
+        let Some(element_bytes) = element_bytes else {
+            return Err(
+                self.invalid_type("is an array of sets, which have no fixed width".to_string())
+            );
+        };
+        if count == 0 {
+            return Err(self.invalid_type("is an array with no elements".to_string()));
+        }
+        let data_min = u64::from(protocol.data_min);
+        let stride = match stride {
+            Some(stride) if stride < element_bytes => {
+                return Err(self.invalid_type(format!(
+                    "has an array stride of {stride} bytes, smaller than its {element_bytes} byte element"
+                )));
+            }
+            Some(stride) if stride % data_min != 0 => {
+                return Err(self.invalid_type(format!(
+                    "has an array stride of {stride} bytes, not aligned to the data_min of {data_min} bytes"
+                )));
+            }
+            Some(stride) => Some(stride),
+            None => element_bytes.checked_next_multiple_of(data_min),
+        };
+        let Some((stride, bytes)) =
+            stride.and_then(|stride| Some((stride, u64::from(count).checked_mul(stride)?)))
+        else {
+            return Err(ElaborationError::AddressOverflow {
+                path: self.path.clone(),
+                address: *running_address,
+            });
+        };
+        self.render_address(bytes, running_address, protocol)?;
+        let start = self.address().unwrap_or_default();
+        let last = start + (u64::from(count) - 1) * stride;
+        self.range = format!("0x{start:x} .. 0x{last:x} ({count} x {stride} bytes)");
+        Ok(())
+    }
+
+    fn render_field_type_set(
+        &mut self,
+        running_address: &mut u64,
+        inherited: &Inherited,
+        protocol: &Protocol,
+        prefix: &str,
+        errors: &mut Vec<ElaborationError>,
+        parallel: bool,
+    ) -> Result<(), ElaborationError> {
+        let start = self.resolve_address(*running_address, protocol)?;
+        *running_address = start;
+        self.assign_address(start);
+        debug!("Set {} assigned address 0x{:x}", self.path, start);
+        if self.contains.is_none() {
+            return Err(ElaborationError::MissingContains {
+                path: self.path.clone(),
+            });
+        }
+        // Names are compared as the identifiers generated code declares for them, which ignore
+        // case like VHDL and collapse spacing and punctuation
+        let mut names = HashSet::new();
+        for field in self.children() {
+            if !names.insert(identifier(&field.name)) {
+                errors.push(ElaborationError::DuplicateName {
+                    path: self.path.clone(),
+                    name: field.name.clone(),
+                });
+            }
+        }
+        if self.pack_bits {
+            Field::render_packed_members(
+                self.children_mut(),
+                running_address,
+                inherited,
+                protocol,
+                prefix,
+                errors,
+                parallel,
+            )?;
+        } else {
+            Field::render_members(
+                self.children_mut(),
+                running_address,
+                inherited,
+                protocol,
+                prefix,
+                errors,
+                parallel,
+            )?;
+        }
+        // Members with a negative address are placed once the others have fixed where the set
+        // would end, which moves out by the furthest of their offsets to make room for them
+        if let Some(tail) = self
+            .children()
+            .iter()
+            .filter_map(Field::offset_from_end)
+            .max()
+        {
+            let end = running_address
+                .checked_next_multiple_of(u64::from(protocol.data_min))
+                .and_then(|end| end.checked_add(tail))
+                .ok_or_else(|| ElaborationError::AddressOverflow {
+                    path: self.path.clone(),
+                    address: *running_address,
+                })?;
+            *running_address = end;
+            for field in self.children_mut() {
+                if let Some(offset) = field.offset_from_end() {
+                    let mut running = end - offset;
+                    field.address = Some(Address::Absolute(running));
+                    field.render_recursive(
+                        &mut running,
+                        inherited,
+                        protocol,
+                        Some(prefix),
+                        errors,
+                        parallel,
+                    )?;
+                    *running_address = (*running_address).max(running);
+                }
+            }
+        }
+        for child in self.children_mut() {
+            child.offset = child
+                .address()
+                .and_then(|address| address.checked_sub(start));
+        }
+        let end = *running_address;
+        self.range = if end > start {
+            format!("0x{:x} .. 0x{:x}", start, end - 1)
+        } else {
+            String::new()
+        };
+        Ok(())
+    }
+
+    /// Renders the members of a set in declaration order, leaving out those with a negative
+    /// address
+    fn render_members(
+        fields: &mut [Field],
+        running_address: &mut u64,
+        inherited: &Inherited,
+        protocol: &Protocol,
+        prefix: &str,
+        errors: &mut Vec<ElaborationError>,
+        parallel: bool,
+    ) -> Result<(), ElaborationError> {
+        #[cfg(feature = "rayon")]
+        if parallel {
+            return Field::render_members_parallel(
+                fields,
+                running_address,
+                inherited,
+                protocol,
+                prefix,
+                errors,
+            );
+        }
+        for field in fields
+            .iter_mut()
+            .filter(|field| field.offset_from_end().is_none())
+        {
+            field.render_recursive(
+                running_address,
+                inherited,
+                protocol,
+                Some(prefix),
+                errors,
+                parallel,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Renders the members of a set like [`Field::render_members`], placing those narrower than
+    /// a byte and without an explicit address in the unused bits of the Protocol.data_min unit
+    /// holding the previous such member
+    fn render_packed_members(
+        fields: &mut [Field],
+        running_address: &mut u64,
+        inherited: &Inherited,
+        protocol: &Protocol,
+        prefix: &str,
+        errors: &mut Vec<ElaborationError>,
+        parallel: bool,
+    ) -> Result<(), ElaborationError> {
+        let unit_bits = u64::from(protocol.data_min) * 8;
+        // Address of the unit being filled and the bits of it used so far
+        let mut unit: Option<(u64, u64)> = None;
+        for field in fields
+            .iter_mut()
+            .filter(|field| field.offset_from_end().is_none())
+        {
+            let Some(bits) = field
+                .field_type
+                .bit_length()
+                .filter(|&bits| bits < 8 && field.address.is_none())
+            else {
+                unit = None;
+                field.render_recursive(
+                    running_address,
+                    inherited,
+                    protocol,
+                    Some(prefix),
+                    errors,
+                    parallel,
+                )?;
+                continue;
+            };
+            let shared = unit.filter(|&(_, used)| used + bits <= unit_bits);
+            let mut running = shared.map_or(*running_address, |(address, _)| address);
+            field.render_recursive(
+                &mut running,
+                inherited,
+                protocol,
+                Some(prefix),
+                errors,
+                parallel,
+            )?;
+            let used = shared.map_or(0, |(_, used)| used);
+            field.bit_offset = Some(used);
+            unit = field.address().map(|address| (address, used + bits));
+            *running_address = (*running_address).max(running);
+        }
+        Ok(())
+    }
+
+    /// Renders the members of a set like [`Field::render_members`], but splits them into runs
+    /// that each start at an explicitly addressed member. A run's layout does not depend on the
+    /// members before it, so runs are rendered concurrently and their results merged in order.
+    #[cfg(feature = "rayon")]
+    fn render_members_parallel(
+        fields: &mut [Field],
+        running_address: &mut u64,
+        inherited: &Inherited,
+        protocol: &Protocol,
+        prefix: &str,
+        errors: &mut Vec<ElaborationError>,
+    ) -> Result<(), ElaborationError> {
+        use rayon::prelude::*;
+        let mut runs = Vec::new();
+        let mut rest = fields;
+        while !rest.is_empty() {
+            let length = rest
+                .iter()
+                .skip(1)
+                .position(|field| field.address().is_some())
+                .map_or(rest.len(), |index| index + 1);
+            let (run, tail) = rest.split_at_mut(length);
+            runs.push(run);
+            rest = tail;
+        }
+        let start = *running_address;
+        let results: Vec<_> = runs
+            .into_par_iter()
+            .map(|run| {
+                let mut running = start;
+                let mut run_errors = Vec::new();
+                let result = run
+                    .iter_mut()
+                    .filter(|field| field.offset_from_end().is_none())
+                    .try_for_each(|field| {
+                        field.render_recursive(
+                            &mut running,
+                            inherited,
+                            protocol,
+                            Some(prefix),
+                            &mut run_errors,
+                            true,
+                        )
+                    });
+                (running, run_errors, result)
+            })
+            .collect();
+        // Matches the serial pass, which stops at the first fatal error
+        for (running, run_errors, result) in results {
+            errors.extend(run_errors);
+            result?;
+            *running_address = running;
+        }
+        Ok(())
+    }
+
+    /// Renders this field and any fields it contains. Problems confined to a single field, such
+    /// as a value outside its range, are collected into `errors` so that elaboration can carry on;
+    /// problems that leave the layout undefined are returned immediately.
+    fn render_recursive(
+        &mut self,
+        running_address: &mut u64,
+        parent: &Inherited,
+        protocol: &Protocol,
+        parent_path: Option<&str>,
+        errors: &mut Vec<ElaborationError>,
+        parallel: bool,
+    ) -> Result<(), ElaborationError> {
+        let access = self.access.unwrap_or(parent.access);
+        self.access = Some(access);
+        if self.unit.is_none() && self.field_type.takes_unit() {
+            self.unit = parent.unit.clone();
+        }
+        self.path = match parent_path {
+            None | Some("") => self.name.clone(),
+            Some(parent_path) => format!("{parent_path}.{}", self.name),
+        };
+        // Like generated names, paths below the top-level set omit the top-level set's name
+        let prefix = match parent_path {
+            None => String::new(),
+            Some(_) => self.path.clone(),
+        };
+        self.warn_values(access);
+        if parent_path.is_none() && self.offset_from_end().is_some() {
+            return Err(ElaborationError::FromEndOutsideSet {
+                path: self.path.clone(),
+            });
+        }
+        // Checked up front since a field too narrow to extend past address_max is not caught
+        // when its address is assigned
+        if let Some(address) = self.address()
+            && address > protocol.address_max
+        {
+            return Err(ElaborationError::AddressBeyondMax {
+                path: self.path.clone(),
+                address,
+                address_max: protocol.address_max,
+            });
+        }
+        if let Some(data_max) = protocol.data_max
+            && let Some(bytes) = self.field_type.access_width(u64::from(protocol.data_min))
+            && bytes > u64::from(data_max)
+        {
+            errors.push(ElaborationError::ExceedsDataMax {
+                path: self.path.clone(),
+                bytes,
+                data_max,
+            });
+        }
+        match self.field_type {
+            FieldType::Set => self.render_field_type_set(
+                running_address,
+                &Inherited {
+                    access,
+                    unit: self.unit.clone(),
+                },
+                protocol,
+                &prefix,
+                errors,
+                parallel,
+            ),
+            FieldType::Boolean => self.render_field_type_boolean(running_address, protocol, errors),
+            FieldType::String(length) => {
+                self.render_field_type_string(length, running_address, protocol, errors)
+            }
+            FieldType::Enum { length, .. } => {
+                self.render_field_type_enum(length, running_address, protocol, errors)
+            }
+            FieldType::Bitfield { length, .. } => {
+                self.render_field_type_bitfield(length, running_address, protocol)
+            }
+            FieldType::Unsigned(length) => {
+                self.render_field_type_unsigned(length, running_address, protocol, errors)
+            }
+            FieldType::Signed(length) => {
+                self.render_field_type_signed(length, running_address, protocol, errors)
+            }
+            FieldType::UFixed { high, low } => {
+                self.render_field_type_ufixed(high, low, running_address, protocol, errors)
+            }
+            FieldType::SFixed { high, low } => {
+                self.render_field_type_sfixed(high, low, running_address, protocol, errors)
+            }
+            FieldType::Array {
+                count,
+                stride,
+                ref element,
+            } => {
+                let element_bytes = element.byte_width(u64::from(protocol.data_min));
+                // Only sets leave an element without a width; otherwise it cannot be addressed
+                if element_bytes.is_none() && !matches!(element.innermost(), FieldType::Set) {
+                    return Err(ElaborationError::AddressOverflow {
+                        path: self.path.clone(),
+                        address: *running_address,
+                    });
+                }
+                if element_bytes.is_some() {
+                    self.check_array_element(element, protocol, errors)?;
+                }
+                self.render_field_type_array(
+                    count,
+                    stride,
+                    element_bytes,
+                    running_address,
+                    protocol,
+                )
+            }
+            FieldType::Reserved(length) => {
+                self.render_field_type_reserved(length, running_address, protocol)
+            }
+        }
+    }
+}
+
+/// Properties a field takes from its enclosing set when it does not give its own
+struct Inherited {
+    access: Access,
+    unit: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(try_from = "MemoryMapSource")]
+pub struct MemoryMap {
+    protocol: Protocol,
+    #[serde(flatten)]
+    field: Field,
+    /// Whether elaboration requires the top-level field to be a set
+    #[serde(skip)]
+    strict: bool,
+}
+
+/// The limit a value falls outside of
+#[derive(Debug, PartialEq)]
+pub enum Bound {
+    Min(f64),
+    Max(f64),
+}
+
+impl fmt::Display for Bound {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bound::Min(min) => write!(fmt, "below its min of {min}"),
+            Bound::Max(max) => write!(fmt, "above its max of {max}"),
+        }
+    }
+}
+
+/// A problem found while elaborating a memory map. `path` names the field by the dotted names
+/// of its enclosing sets below the top-level set.
+#[derive(Debug, Error)]
+pub enum ElaborationError {
+    #[error("No protocol was given for the memory map")]
+    MissingProtocol,
+    #[error("No memory maps to merge into {name}")]
+    NoMaps { name: String },
+    #[error("Memory map {name} declares a protocol that conflicts with the protocol of {first}")]
+    ConflictingProtocol { name: String, first: String },
+    #[error("Protocol dataMin must be at least 1 byte")]
+    ZeroDataMin,
+    #[error(
+        "Protocol registerAlign of {register_align} bytes is not a nonzero multiple of dataMin"
+    )]
+    InvalidRegisterAlign { register_align: u8 },
+    #[error(
+        "Protocol baseAddress 0x{base_address:x} is not a multiple of registerAlign, or of \
+         dataMin when registerAlign is not set"
+    )]
+    MisalignedBase { base_address: u64 },
+    #[error(
+        "Top-level field {name} is a {field_type} rather than a set; move it into the contains \
+         of a top-level set so that it is laid out like any other register"
+    )]
+    RootNotSet { name: String, field_type: String },
+    #[error("Field {path} min of {min} is greater than its max of {max}")]
+    InvertedBounds { path: String, min: f64, max: f64 },
+    #[error("Field {path} {label} {value} does not fit in its {field_type} type")]
+    OutsideType {
+        path: String,
+        /// Whether the value or the reset value does not fit
+        label: &'static str,
+        value: String,
+        field_type: String,
+    },
+    #[error("Field {path} {label} {value} is {bound}")]
+    ValueOutOfRange {
+        path: String,
+        /// Whether the value or the reset value is out of range
+        label: &'static str,
+        value: f64,
+        bound: Bound,
+    },
+    #[error(
+        "Field {path} {label} {value} is not a variant of its enum; valid variants are: {}",
+        variants.join(", ")
+    )]
+    UnknownVariant {
+        path: String,
+        label: &'static str,
+        /// The offending value as JSON
+        value: String,
+        variants: Vec<String>,
+    },
+    #[error(
+        "Field {path} is an enum of {length} bits, too short for its largest index {max_index}; \
+         the length must be at least {required}"
+    )]
+    EnumTooShort {
+        path: String,
+        length: u64,
+        max_index: u64,
+        required: u64,
+    },
+    #[error("Field {path} is a boolean and its {label} must be true or false")]
+    TypeMismatch { path: String, label: &'static str },
+    #[error("Field {path} {reason}")]
+    InvalidType { path: String, reason: String },
+    #[error(
+        "Field {path} address 0x{address:x} is not aligned to the data_min of {data_min} bytes"
+    )]
+    Misaligned {
+        path: String,
+        address: u64,
+        data_min: u64,
+    },
+    #[error(
+        "Field {path} address 0x{address:x} is not aligned to the registerAlign of {register_align} bytes"
+    )]
+    MisalignedRegister {
+        path: String,
+        address: u64,
+        register_align: u64,
+    },
+    #[error(
+        "Field {path} address 0x{address:x} overlaps the preceding field, which ends at 0x{end:x}"
+    )]
+    Overlap {
+        path: String,
+        address: u64,
+        end: u64,
+    },
+    #[error("Field {path} has a negative address but is not a member of a set")]
+    FromEndOutsideSet { path: String },
+    #[error("Field {path} at 0x{address:x} overflows the address space")]
+    AddressOverflow { path: String, address: u64 },
+    #[error(
+        "Field {path} is {bytes} bytes wide, more than the dataMax of {data_max} bytes; split it \
+         into a set of narrower fields"
+    )]
+    ExceedsDataMax {
+        path: String,
+        bytes: u64,
+        data_max: u8,
+    },
+    #[error("Field {path} address 0x{address:x} is beyond the maximum address 0x{address_max:x}")]
+    AddressBeyondMax {
+        path: String,
+        address: u64,
+        address_max: u64,
+    },
+    #[error("Field {path} at 0x{address:x} extends past the maximum address 0x{address_max:x}")]
+    ExceedsAddressMax {
+        path: String,
+        address: u64,
+        address_max: u64,
+    },
+    #[error("Set {path} does not contain any fields")]
+    MissingContains { path: String },
+    #[error("Set {path} contains more than one field named {name}")]
+    DuplicateName { path: String, name: String },
+}
+
+/// Every problem found while elaborating a memory map
+#[derive(Debug)]
+pub struct ElaborationReport {
+    errors: Vec<ElaborationError>,
+}
+
+impl ElaborationReport {
+    /// The problems in the order they were found
+    pub fn errors(&self) -> &[ElaborationError] {
+        &self.errors
+    }
+
+    /// Consumes the report, returning its problems in the order they were found
+    pub fn into_errors(self) -> Vec<ElaborationError> {
+        self.errors
+    }
+}
+
+/// Lists one problem per line
+impl fmt::Display for ElaborationReport {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, error) in self.errors.iter().enumerate() {
+            if index > 0 {
+                fmt.write_str("\n")?;
+            }
+            write!(fmt, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ElaborationReport {}
+
+/// A report of a single problem
+impl From<ElaborationError> for ElaborationReport {
+    fn from(error: ElaborationError) -> Self {
+        ElaborationReport {
+            errors: vec![error],
+        }
+    }
+}
+
+impl Protocol {
+    /// Optional name of the protocol
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Maximum address in terms of dataMin
+    pub fn address_max(&self) -> u64 {
+        self.address_max
+    }
+
+    /// Minimum addressable data size in bytes
+    pub fn data_min(&self) -> u8 {
+        self.data_min
+    }
+
+    /// Boundary in bytes that fields other than sets are aligned to, when it differs from
+    /// dataMin
+    pub fn register_align(&self) -> Option<u8> {
+        self.register_align
+    }
+
+    /// Widest field in bytes that the bus can access in a single transaction
+    pub fn data_max(&self) -> Option<u8> {
+        self.data_max
+    }
+
+    /// Byte order of fields wider than one byte
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Address the map is relocated to
+    pub fn base_address(&self) -> Option<u64> {
+        self.base_address
+    }
+}
+
+impl MemoryMap {
+    /// Protocol of the memory map
+    pub fn protocol(&self) -> &Protocol {
+        &self.protocol
+    }
+
+    /// Top-level field of the memory map
+    pub fn root(&self) -> &Field {
+        &self.field
+    }
+
+    /// Makes elaboration fail unless the top-level field is a set, as most consumers of the
+    /// generated documents expect. Off by default.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Assigns an address to every field, resolves inherited access and renders each field's
+    /// range, validating the layout against the protocol. Elaboration continues past problems
+    /// confined to a single field so that all of them are reported together. Addresses are
+    /// assigned afresh from those given on input, so elaborating again yields the same layout.
+    pub fn elaborate(&mut self) -> Result<(), ElaborationReport> {
+        self.elaborate_with(false)
+    }
+
+    /// Elaborates like [`MemoryMap::elaborate`], rendering explicitly addressed members of
+    /// each set concurrently. The result is identical to the serial pass.
+    #[cfg(feature = "rayon")]
+    pub fn elaborate_parallel(&mut self) -> Result<(), ElaborationReport> {
+        self.elaborate_with(true)
+    }
+
+    fn elaborate_with(&mut self, parallel: bool) -> Result<(), ElaborationReport> {
+        let mut errors = Vec::new();
+        if self.strict && !matches!(self.field.field_type, FieldType::Set) {
+            errors.push(ElaborationError::RootNotSet {
+                name: self.field.name.clone(),
+                field_type: self.field.field_type.to_string(),
+            });
+        }
+        if self.protocol.data_min == 0 {
+            errors.push(ElaborationError::ZeroDataMin);
+        } else if let Some(register_align) = self.protocol.register_align
+            && (register_align == 0 || !register_align.is_multiple_of(self.protocol.data_min))
+        {
+            errors.push(ElaborationError::InvalidRegisterAlign { register_align });
+        } else if let Some(base_address) = self.protocol.base_address
+            && !base_address.is_multiple_of(u64::from(
+                self.protocol
+                    .register_align
+                    .unwrap_or(self.protocol.data_min),
+            ))
+        {
+            errors.push(ElaborationError::MisalignedBase { base_address });
+        } else {
+            let base = self.protocol.base_address.unwrap_or(0);
+            self.field.restore_declared_addresses(base);
+            let mut running_address = base;
+            let result = self.field.render_recursive(
+                &mut running_address,
+                &Inherited {
+                    access: Access::ReadWrite,
+                    unit: None,
+                },
+                &self.protocol,
+                None,
+                &mut errors,
+                parallel,
+            );
+            errors.extend(result.err());
+        }
+        if errors.is_empty() {
+            errors.extend(self.check_overlaps());
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ElaborationReport { errors })
+        }
+    }
+}
+
+/// Input form of a MemoryMap, which additionally accepts a flat register table
+#[derive(Deserialize, JsonSchema)]
+#[schemars(
+    deny_unknown_fields,
+    title = "VHDL Memory Map",
+    description = "Register layout of a hardware block and the bus protocol used to access it, \
+                   documented and exported to VHDL by vhdl_doc"
+)]
+struct MemoryMapSource {
+    protocol: Protocol,
+    /// Compact alternative to Field.contains for flat register banks.
+    /// Each entry becomes a child of the top-level set, which must have type `set`.
+    /// Mutually exclusive with a top-level `contains`.
+    registers: Option<Vec<Field>>,
+    #[serde(flatten)]
+    field: Field,
+    /// Keys left over once the flattened field has taken its own, since serde cannot deny
+    /// unknown fields alongside `flatten`
+    #[serde(flatten)]
+    #[schemars(skip)]
+    unknown: BTreeMap<String, IgnoredAny>,
+}
+
+impl TryFrom<MemoryMapSource> for MemoryMap {
+    type Error = String;
+
+    fn try_from(source: MemoryMapSource) -> Result<Self, Self::Error> {
+        if let Some(key) = source.unknown.keys().next() {
+            return Err(format!(
+                "unknown field `{key}`, expected `protocol`, `registers` or a field key"
+            ));
+        }
+        let mut field = source.field;
+        if let Some(registers) = source.registers {
+            if field.contains.is_some() {
+                return Err("`registers` and `contains` are mutually exclusive".to_string());
+            }
+            if !matches!(field.field_type, FieldType::Set) {
+                return Err(
+                    "`registers` requires the top-level field to have type `set`".to_string(),
+                );
+            }
+            field.contains = Some(OneOrMoreField::More(registers));
+        }
+        Ok(MemoryMap {
+            protocol: source.protocol,
+            field,
+            strict: false,
+        })
+    }
+}
+
+/// Layout of generated JSON
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JsonStyle {
+    /// No whitespace between tokens
+    Compact,
+    /// One value per line, nested values indented by the given number of spaces
+    Indent(usize),
+}
+
+/// Four spaces of indentation, as generated JSON has always been written
+impl Default for JsonStyle {
+    fn default() -> Self {
+        JsonStyle::Indent(4)
+    }
+}
+
+/// Streams a value to `out` as JSON laid out according to `style`
+fn write_styled_json<T: Serialize, W: io::Write>(
+    value: &T,
+    style: JsonStyle,
+    out: &mut W,
+) -> Result<(), serde_json::Error> {
+    match style {
+        JsonStyle::Compact => serde_json::to_writer(out, value),
+        JsonStyle::Indent(width) => {
+            let indent = vec![b' '; width];
+            let formatter = PrettyFormatter::with_indent(&indent);
+            let mut ser = serde_json::Serializer::with_formatter(out, formatter);
+            value.serialize(&mut ser)
+        }
+    }
+}
+
+/// Serializes a value as JSON laid out according to `style`
+fn styled_json<T: Serialize>(value: &T, style: JsonStyle) -> Result<String, serde_json::Error> {
+    let mut buf = Vec::new();
+    write_styled_json(value, style, &mut buf)?;
+    Ok(String::from_utf8(buf).expect("Failed to convert serial buffer to string"))
+}
+
+/// Collects the output of a writer based renderer into a String
+fn render_to_string(render: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> String {
+    let mut buf = Vec::new();
+    render(&mut buf).expect("Writing to a Vec cannot fail");
+    String::from_utf8(buf).expect("Rendered output is valid UTF-8")
+}
+
+impl MemoryMap {
+    /// Combines memory maps sharing one protocol into a single map whose top-level set,
+    /// called `name`, contains the top-level field of each map in order
+    pub fn merge(name: &str, maps: Vec<MemoryMap>) -> Result<MemoryMap, ElaborationError> {
+        let mut maps = maps.into_iter();
+        let first = maps.next().ok_or_else(|| ElaborationError::NoMaps {
+            name: name.to_string(),
+        })?;
+        let mut fields = vec![first.field];
+        for map in maps {
+            if map.protocol != first.protocol {
+                return Err(ElaborationError::ConflictingProtocol {
+                    name: map.field.name,
+                    first: fields[0].name.clone(),
+                });
+            }
+            fields.push(map.field);
+        }
+        Ok(MemoryMap {
+            protocol: first.protocol,
+            field: Field {
+                contains: Some(OneOrMoreField::More(fields)),
+                ..Field::new(name, FieldType::Set)
+            },
+            strict: first.strict,
+        })
+    }
+
+    /// Highest assigned address plus the width of the field occupying it, i.e. the number of
+    /// bytes from address 0 to the end of the map. Returns 0 before elaboration.
+    pub fn size_bytes(&self) -> u64 {
+        self.field.end_address(&self.protocol).unwrap_or(0)
+    }
+
+    /// Number of fields in the memory map, counting every set and its members
+    pub fn field_count(&self) -> usize {
+        fn count(field: &Field) -> usize {
+            1 + field.children().iter().map(count).sum::<usize>()
+        }
+        count(&self.field)
+    }
+
+    /// Serializes the memory map as JSON indented by four spaces
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        self.to_json_styled(JsonStyle::default())
+    }
+
+    /// Streams the JSON produced by [`MemoryMap::to_json`] to `out`
+    pub fn write_json<W: io::Write>(&self, out: &mut W) -> Result<(), serde_json::Error> {
+        self.write_json_styled(JsonStyle::default(), out)
+    }
+
+    /// Serializes the memory map as JSON laid out according to `style`
+    pub fn to_json_styled(&self, style: JsonStyle) -> Result<String, serde_json::Error> {
+        styled_json(self, style)
+    }
+
+    /// Streams the JSON produced by [`MemoryMap::to_json_styled`] to `out`
+    pub fn write_json_styled<W: io::Write>(
+        &self,
+        style: JsonStyle,
+        out: &mut W,
+    ) -> Result<(), serde_json::Error> {
+        write_styled_json(self, style, out)
+    }
+}
+
+pub fn get_memory_map_schema() -> String {
+    get_memory_map_schema_styled(JsonStyle::default())
+}
+
+/// The memory map JSON schema laid out according to `style`
+pub fn get_memory_map_schema_styled(style: JsonStyle) -> String {
+    let schema = SchemaSettings::draft2020_12()
+        .into_generator()
+        .into_root_schema_for::<MemoryMap>();
+    styled_json(&schema, style).expect("Failed to serialize schema")
 }
@@ -0,0 +1,262 @@
+use super::{
+    Access, Address, BitfieldStyle, ElaborationError, ElaborationReport, Endianness, Field,
+    FieldType, MemoryMap, OneOrMoreField, Protocol, Value,
+};
+
+/// Builds a [`MemoryMap`] programmatically rather than by deserializing text. The protocol
+/// is set with [`MemoryMapBuilder::protocol`] and fields are added to the top-level set with
+/// [`MemoryMapBuilder::field`].
+pub struct MemoryMapBuilder {
+    protocol: Protocol,
+    /// Whether [`MemoryMapBuilder::protocol`] has been called
+    has_protocol: bool,
+    root: FieldBuilder,
+}
+
+impl MemoryMapBuilder {
+    /// Starts a memory map whose top-level set is called `name`
+    pub fn new(name: &str) -> MemoryMapBuilder {
+        MemoryMapBuilder {
+            protocol: Protocol {
+                name: None,
+                address_max: 0,
+                data_min: 0,
+                register_align: None,
+                data_max: None,
+                endianness: Endianness::default(),
+                base_address: None,
+            },
+            has_protocol: false,
+            root: FieldBuilder::new(name),
+        }
+    }
+
+    /// Sets the maximum address, in terms of `data_min`, and the minimum addressable data
+    /// size in bytes of the protocol. Required before [`MemoryMapBuilder::build`].
+    pub fn protocol(mut self, address_max: u64, data_min: u8) -> MemoryMapBuilder {
+        self.protocol.address_max = address_max;
+        self.protocol.data_min = data_min;
+        self.has_protocol = true;
+        self
+    }
+
+    /// Names the protocol
+    pub fn protocol_name(mut self, name: &str) -> MemoryMapBuilder {
+        self.protocol.name = Some(name.to_string());
+        self
+    }
+
+    /// Sets the boundary in bytes that every field other than a set is aligned to
+    pub fn register_align(mut self, register_align: u8) -> MemoryMapBuilder {
+        self.protocol.register_align = Some(register_align);
+        self
+    }
+
+    /// Sets the widest field in bytes that the bus can access in a single transaction
+    pub fn data_max(mut self, data_max: u8) -> MemoryMapBuilder {
+        self.protocol.data_max = Some(data_max);
+        self
+    }
+
+    /// Sets the byte order of fields wider than one byte
+    pub fn endianness(mut self, endianness: Endianness) -> MemoryMapBuilder {
+        self.protocol.endianness = endianness;
+        self
+    }
+
+    /// Relocates the map to start at `base_address`
+    pub fn base_address(mut self, base_address: u64) -> MemoryMapBuilder {
+        self.protocol.base_address = Some(base_address);
+        self
+    }
+
+    /// Describes the top-level set
+    pub fn description(mut self, description: &str) -> MemoryMapBuilder {
+        self.root = self.root.description(description);
+        self
+    }
+
+    /// Adds a field to the top-level set
+    pub fn field(mut self, field: FieldBuilder) -> MemoryMapBuilder {
+        self.root = self.root.field(field);
+        self
+    }
+
+    /// Assembles and elaborates the memory map, failing if no protocol was given or
+    /// elaboration reports any problem
+    pub fn build(self) -> Result<MemoryMap, ElaborationReport> {
+        if !self.has_protocol {
+            return Err(ElaborationError::MissingProtocol.into());
+        }
+        let mut memory_map = MemoryMap {
+            protocol: self.protocol,
+            field: self.root.build(),
+            strict: false,
+        };
+        memory_map.elaborate()?;
+        Ok(memory_map)
+    }
+}
+
+/// Builds a [`Field`] for a [`MemoryMapBuilder`]. A field is a set until one of the type
+/// methods makes it something else; the fields of a set are added with
+/// [`FieldBuilder::field`].
+pub struct FieldBuilder {
+    field: Field,
+    children: Vec<FieldBuilder>,
+}
+
+impl FieldBuilder {
+    /// Starts a set called `name`
+    pub fn new(name: &str) -> FieldBuilder {
+        FieldBuilder {
+            field: Field::new(name, FieldType::Set),
+            children: Vec::new(),
+        }
+    }
+
+    /// Gives the field an arbitrary type
+    pub fn field_type(mut self, field_type: FieldType) -> FieldBuilder {
+        self.field.field_type = field_type;
+        self
+    }
+
+    /// Makes the field a boolean
+    pub fn boolean(self) -> FieldBuilder {
+        self.field_type(FieldType::Boolean)
+    }
+
+    /// Makes the field a string of `length` bytes
+    pub fn string(self, length: u64) -> FieldBuilder {
+        self.field_type(FieldType::String(length))
+    }
+
+    /// Makes the field an unsigned integer of `bits` bits
+    pub fn unsigned(self, bits: u64) -> FieldBuilder {
+        self.field_type(FieldType::Unsigned(bits))
+    }
+
+    /// Makes the field a signed integer of `bits` bits
+    pub fn signed(self, bits: u64) -> FieldBuilder {
+        self.field_type(FieldType::Signed(bits))
+    }
+
+    /// Makes the field an enum of `length` bits with the given variant names and indices
+    pub fn enumeration<'a>(
+        self,
+        length: u64,
+        variants: impl IntoIterator<Item = (&'a str, u64)>,
+    ) -> FieldBuilder {
+        let map = variants
+            .into_iter()
+            .map(|(name, index)| (name.to_string(), index))
+            .collect();
+        self.field_type(FieldType::Enum { length, map })
+    }
+
+    /// Makes the field a bitfield of `length` bits naming the bits at the given indices
+    pub fn bitfield<'a>(
+        self,
+        length: u64,
+        bits: impl IntoIterator<Item = (&'a str, u64)>,
+    ) -> FieldBuilder {
+        let bits = bits
+            .into_iter()
+            .map(|(name, index)| (name.to_string(), index))
+            .collect();
+        self.field_type(FieldType::Bitfield {
+            length,
+            bits: BitfieldStyle::Discrete(bits),
+        })
+    }
+
+    /// Makes the field a bitfield of `length` bits naming consecutive bits from bit 0
+    pub fn bits<'a>(self, length: u64, names: impl IntoIterator<Item = &'a str>) -> FieldBuilder {
+        let names = names.into_iter().map(str::to_string).collect();
+        self.field_type(FieldType::Bitfield {
+            length,
+            bits: BitfieldStyle::FromZero(names),
+        })
+    }
+
+    /// Describes the field
+    pub fn description(mut self, description: &str) -> FieldBuilder {
+        self.field.description = Some(description.to_string());
+        self
+    }
+
+    /// Places the field at `address` rather than packing it after the previous field
+    pub fn at(mut self, address: u64) -> FieldBuilder {
+        self.field.address = Some(Address::Absolute(address));
+        self
+    }
+
+    /// Places the field `offset` bytes back from the end of its set
+    pub fn from_end(mut self, offset: u64) -> FieldBuilder {
+        self.field.address = Some(Address::FromEnd(offset));
+        self
+    }
+
+    /// Sets the access permission rather than inheriting it from the enclosing set
+    pub fn access(mut self, access: Access) -> FieldBuilder {
+        self.field.access = Some(access);
+        self
+    }
+
+    /// Sets the default value
+    pub fn value(mut self, value: Value) -> FieldBuilder {
+        self.field.value = Some(value);
+        self
+    }
+
+    /// Sets the reset value, when it differs from the default value
+    pub fn reset(mut self, reset: Value) -> FieldBuilder {
+        self.field.reset = Some(reset);
+        self
+    }
+
+    /// Sets the unit of measurement of a numeric type
+    pub fn unit(mut self, unit: &str) -> FieldBuilder {
+        self.field.unit = Some(unit.to_string());
+        self
+    }
+
+    /// Sets the minimum allowed value of a numeric type
+    pub fn min(mut self, min: f64) -> FieldBuilder {
+        self.field.min = Some(min);
+        self
+    }
+
+    /// Sets the maximum allowed value of a numeric type
+    pub fn max(mut self, max: f64) -> FieldBuilder {
+        self.field.max = Some(max);
+        self
+    }
+
+    /// Packs members of this set narrower than a byte into shared bytes
+    pub fn pack_bits(mut self) -> FieldBuilder {
+        self.field.pack_bits = true;
+        self
+    }
+
+    /// Adds a field to this set
+    pub fn field(mut self, field: FieldBuilder) -> FieldBuilder {
+        self.children.push(field);
+        self
+    }
+
+    /// Assembles the field, holding a lone member of a set the way a single field object is
+    /// deserialized
+    fn build(self) -> Field {
+        let mut field = self.field;
+        let mut children: Vec<Field> = self.children.into_iter().map(FieldBuilder::build).collect();
+        field.contains = match children.len() {
+            0 => None,
+            1 => children
+                .pop()
+                .map(|child| OneOrMoreField::One(Box::new(child))),
+            _ => Some(OneOrMoreField::More(children)),
+        };
+        field
+    }
+}
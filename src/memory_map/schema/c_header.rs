@@ -0,0 +1,277 @@
+use super::{
+    Field, FieldType, MemoryMap, Protocol, SharedTypes, hex_digits, identifier, render_to_string,
+    sorted_variants,
+};
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+const C_KEYWORDS: &[&str] = &[
+    "auto", "bool", "break", "case", "char", "const", "continue", "default", "do", "double",
+    "else", "enum", "extern", "false", "float", "for", "goto", "if", "inline", "int", "long",
+    "register", "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch",
+    "true", "typedef", "union", "unsigned", "void", "volatile", "while",
+];
+
+/// Lower case C identifier for struct types and members
+fn member_identifier(name: &str) -> String {
+    let member = identifier(name).to_lowercase();
+    if C_KEYWORDS.contains(&member.as_str()) {
+        format!("{member}_")
+    } else {
+        member
+    }
+}
+
+/// Formats an unsigned hex constant padded to `digits`, with a suffix wide enough for 64 bits
+/// when needed
+fn hex_literal(value: u64, digits: usize) -> String {
+    if digits > 8 {
+        format!("0x{value:0digits$X}ull")
+    } else {
+        format!("0x{value:0digits$X}u")
+    }
+}
+
+/// Fixed-size integer type holding exactly `bytes` bytes, if one exists
+fn fixed_type(bytes: u64, signed: bool) -> Option<&'static str> {
+    match (bytes, signed) {
+        (1, false) => Some("uint8_t"),
+        (2, false) => Some("uint16_t"),
+        (4, false) => Some("uint32_t"),
+        (8, false) => Some("uint64_t"),
+        (1, true) => Some("int8_t"),
+        (2, true) => Some("int16_t"),
+        (4, true) => Some("int32_t"),
+        (8, true) => Some("int64_t"),
+        _ => None,
+    }
+}
+
+/// Whether values of the type are two's complement
+fn is_signed(field_type: &FieldType) -> bool {
+    matches!(field_type, FieldType::Signed(_) | FieldType::SFixed { .. })
+}
+
+/// Member declaration of `bytes` bytes at `offset` in a struct overlay along with its
+/// alignment, using a fixed-size integer type when one of that width is naturally aligned
+fn integer_member(name: &str, offset: u64, bytes: u64, signed: bool) -> (String, u64) {
+    match fixed_type(bytes, signed).filter(|_| offset.is_multiple_of(bytes)) {
+        Some(member_type) => (format!("{member_type} {name};"), bytes),
+        None => (format!("uint8_t {name}[{bytes}];"), 1),
+    }
+}
+
+impl Field {
+    /// Writes the address, bitfield and enum macros for this field and any fields it contains.
+    /// Bitfields and enums identical to one already in `types` refer to its macros instead of
+    /// repeating them.
+    fn c_defines<'a, W: Write>(
+        &'a self,
+        prefix: &str,
+        digits: usize,
+        types: &mut SharedTypes<'a>,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let prefixed = match prefix {
+            "" => identifier(&self.name),
+            _ => format!("{prefix}_{}", identifier(&self.name)),
+        };
+        if matches!(self.field_type, FieldType::Set) {
+            writeln!(out, "\n/* {} */", self.name)?;
+        }
+        if let Some(address) = self.address()
+            && !matches!(self.field_type, FieldType::Reserved(_))
+        {
+            let note = self
+                .access
+                .and_then(|access| access.side_effect())
+                .map(|note| format!(" /* {note} */"))
+                .unwrap_or_default();
+            writeln!(
+                out,
+                "#define {prefixed}_ADDR {}{note}",
+                hex_literal(address, digits)
+            )?;
+        }
+        if let Some((low, length)) = self.packed_bits() {
+            let mask_digits = (low + length).div_ceil(8) as usize * 2;
+            writeln!(out, "#define {prefixed}_SHIFT {low}u")?;
+            writeln!(
+                out,
+                "#define {prefixed}_MASK {}",
+                hex_literal(((1 << length) - 1) << low, mask_digits)
+            )?;
+        }
+        if let Some(shared) = types.shared(&self.field_type, &prefixed) {
+            writeln!(out, "/* {prefixed} uses the {shared} definitions */")?;
+        } else {
+            self.c_type_defines(&prefixed, out)?;
+        }
+        for child in self.children() {
+            child.c_defines(&prefixed, digits, types, out)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the bitfield or enum macros describing this field's type
+    fn c_type_defines<W: Write>(&self, prefixed: &str, out: &mut W) -> io::Result<()> {
+        match &self.field_type {
+            FieldType::Bitfield { length, bits } => {
+                let mask_digits = length.div_ceil(4) as usize;
+                for (&index, name) in bits.named_bits().range(..(*length).min(64)) {
+                    let bit = format!("{prefixed}_{}", identifier(name));
+                    writeln!(out, "#define {bit}_SHIFT {index}u")?;
+                    writeln!(
+                        out,
+                        "#define {bit}_MASK {}",
+                        hex_literal(1 << index, mask_digits)
+                    )?;
+                }
+            }
+            FieldType::Enum { map, .. } => {
+                for (value, name) in sorted_variants(map) {
+                    writeln!(out, "#define {prefixed}_{} {value}u", identifier(name))?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Member declaration of a leaf field in a struct overlay along with its alignment. Fields
+    /// whose width matches a fixed-size integer at a naturally aligned offset use that type,
+    /// signed for signed and sfixed values, and everything else is described as bytes.
+    fn c_member(&self, name: &str, offset: u64, bytes: u64, data_min: u64) -> (String, u64) {
+        if let FieldType::Array {
+            count,
+            stride,
+            element,
+        } = &self.field_type
+        {
+            if let Some(width) = element.byte_width(data_min)
+                && !matches!(**element, FieldType::Array { .. })
+                && stride.unwrap_or(width.next_multiple_of(data_min)) == width
+                && let Some(element_type) =
+                    fixed_type(width, is_signed(element)).filter(|_| offset.is_multiple_of(width))
+            {
+                return (format!("{element_type} {name}[{count}];"), width);
+            }
+            return (format!("uint8_t {name}[{bytes}];"), 1);
+        }
+        integer_member(name, offset, bytes, is_signed(&self.field_type))
+    }
+
+    /// Appends a struct overlay for this set, preceded by the overlays of any nested sets.
+    /// Fields packed into the same Protocol.data_min unit by Field.packBits share a single
+    /// member, named after each of them and accessed with their shift and mask macros. Returns
+    /// the struct alignment, or None if the set's members overlap or are out of order and
+    /// therefore cannot be overlaid.
+    fn c_struct(&self, type_name: &str, protocol: &Protocol, out: &mut String) -> Option<u64> {
+        let base = self.address()?;
+        let data_min = u64::from(protocol.data_min);
+        let mut nested = String::new();
+        let mut members = String::new();
+        let mut cursor = 0;
+        let mut alignment = 1;
+        let mut reserved = 0;
+        // Reserved space is left to the padding written before the next member
+        for group in self
+            .member_groups()
+            .filter(|group| !matches!(group[0].field_type, FieldType::Reserved(_)))
+        {
+            let child = &group[0];
+            let offset = child.address()?.checked_sub(base)?;
+            if offset < cursor {
+                return None;
+            }
+            if offset > cursor {
+                writeln!(
+                    members,
+                    "    uint8_t reserved{reserved}[{}];",
+                    offset - cursor
+                )
+                .unwrap();
+                reserved += 1;
+            }
+            if child.bit_offset.is_some() {
+                let names: Vec<String> = group
+                    .iter()
+                    .map(|field| member_identifier(&field.name))
+                    .collect();
+                let (declaration, align) =
+                    integer_member(&names.join("_"), offset, data_min, false);
+                writeln!(members, "    {declaration}").unwrap();
+                cursor = offset + data_min;
+                alignment = alignment.max(align);
+                continue;
+            }
+            let name = member_identifier(&child.name);
+            let bytes = child.end_address(protocol)? - child.address()?;
+            let (declaration, align) = match child.field_type {
+                FieldType::Set => {
+                    let child_type = format!("{type_name}_{name}");
+                    let mut child_struct = String::new();
+                    match child.c_struct(&child_type, protocol, &mut child_struct) {
+                        Some(align) if offset.is_multiple_of(align) => {
+                            nested.push_str(&child_struct);
+                            (format!("{child_type}_t {name};"), align)
+                        }
+                        _ => (format!("uint8_t {name}[{bytes}];"), 1),
+                    }
+                }
+                _ => child.c_member(&name, offset, bytes, data_min),
+            };
+            writeln!(members, "    {declaration}").unwrap();
+            cursor = offset + bytes;
+            alignment = alignment.max(align);
+        }
+        if members.is_empty() {
+            return None;
+        }
+        out.push_str(&nested);
+        writeln!(out, "typedef struct {{\n{members}}} {type_name}_t;\n").unwrap();
+        Some(alignment)
+    }
+}
+
+impl MemoryMap {
+    /// Renders a C header wrapped in the include guard `guard`, defining an address macro for
+    /// every field other than reserved space, shift and mask macros for every named bitfield
+    /// bit and every field packed by Field.packBits, and a value macro for every enum variant.
+    /// Sets are additionally described by struct overlays using fixed-size integer types where
+    /// the layout allows. Must be called after [`MemoryMap::elaborate`] so that addresses are
+    /// populated.
+    pub fn to_c_header(&self, guard: &str) -> String {
+        render_to_string(|out| self.write_c_header(guard, out))
+    }
+
+    /// Streams the header rendered by [`MemoryMap::to_c_header`] to `out`
+    pub fn write_c_header<W: Write>(&self, guard: &str, out: &mut W) -> io::Result<()> {
+        let digits = hex_digits(self.protocol.address_max);
+        writeln!(
+            out,
+            "/* {} register definitions generated by vhdl_doc */",
+            self.field.name
+        )?;
+        writeln!(
+            out,
+            "#ifndef {guard}\n#define {guard}\n\n#include <stdint.h>"
+        )?;
+        let mut types = SharedTypes::default();
+        if matches!(self.field.field_type, FieldType::Set) {
+            for child in self.field.children() {
+                child.c_defines("", digits, &mut types, out)?;
+            }
+            writeln!(out)?;
+            let type_name = member_identifier(&self.field.name);
+            let mut overlay = String::new();
+            self.field
+                .c_struct(&type_name, &self.protocol, &mut overlay);
+            out.write_all(overlay.as_bytes())?;
+        } else {
+            self.field.c_defines("", digits, &mut types, out)?;
+            writeln!(out)?;
+        }
+        writeln!(out, "#endif /* {guard} */")
+    }
+}
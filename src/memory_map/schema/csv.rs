@@ -0,0 +1,67 @@
+use super::{Field, MemoryMap, Protocol, hex_digits, render_to_string};
+use std::io::{self, Write};
+
+/// Column headings of the CSV export
+const CSV_HEADER: &str = "path,address,bits,access,reset,unit,range,description";
+
+/// Quotes a CSV cell containing a separator, quote or line break, doubling embedded quotes
+fn csv_cell(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+impl Field {
+    /// Writes the CSV row of a leaf field
+    fn write_csv_row<W: Write>(
+        &self,
+        protocol: &Protocol,
+        digits: usize,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let bits = self
+            .field_type
+            .bit_length()
+            .unwrap_or_else(|| self.size_bytes(protocol) * 8);
+        let cells = [
+            csv_cell(&self.path),
+            format!("0x{:0digits$X}", self.address().unwrap_or(0)),
+            bits.to_string(),
+            self.access
+                .map(|access| access.to_string())
+                .unwrap_or_default(),
+            csv_cell(
+                &self
+                    .reset_value()
+                    .map(|value| value.to_string())
+                    .unwrap_or_default(),
+            ),
+            csv_cell(self.unit.as_deref().unwrap_or("")),
+            csv_cell(&self.range),
+            csv_cell(self.description.as_deref().unwrap_or("")),
+        ];
+        writeln!(out, "{}", cells.join(","))
+    }
+}
+
+impl MemoryMap {
+    /// Renders a CSV table with a header row followed by one row per field other than a set,
+    /// ordered like [`MemoryMap::flatten`]. Cells holding a comma, quote or line break are
+    /// quoted as described by RFC 4180. Must be called after [`MemoryMap::elaborate`] so that
+    /// addresses, paths and ranges are populated.
+    pub fn to_csv(&self) -> String {
+        render_to_string(|out| self.write_csv(out))
+    }
+
+    /// Streams the table rendered by [`MemoryMap::to_csv`] to `out`
+    pub fn write_csv<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let digits = hex_digits(self.protocol.address_max);
+        writeln!(out, "{CSV_HEADER}")?;
+        for field in self.leaves() {
+            field.write_csv_row(&self.protocol, digits, out)?;
+        }
+        Ok(())
+    }
+}
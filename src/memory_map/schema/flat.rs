@@ -0,0 +1,97 @@
+use super::{Access, ElaborationError, Field, FieldType, MemoryMap};
+use serde::Serialize;
+
+/// A leaf field of an elaborated memory map
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FlatField {
+    /// Dotted names of the enclosing sets below the top-level set, followed by the field name
+    pub path: String,
+    pub address: u64,
+    /// Bytes occupied by the field
+    pub bytes: u64,
+    pub access: Access,
+    /// Rendered range of the field, as in Field.range
+    pub range: String,
+    /// Bytes from the enclosing set's address, as in Field.offset
+    pub offset: Option<u64>,
+    /// Bit of the address at which a packed field starts, as in Field.bitOffset
+    pub bit_offset: Option<u64>,
+}
+
+impl Field {
+    /// Collects this field if it is an elaborated leaf, otherwise each leaf it contains
+    fn leaves_into<'a>(&'a self, out: &mut Vec<&'a Field>) {
+        if matches!(self.field_type, FieldType::Set) {
+            for child in self.children() {
+                child.leaves_into(out);
+            }
+        } else if self.address().is_some() && self.access.is_some() {
+            out.push(self);
+        }
+    }
+}
+
+impl MemoryMap {
+    /// The fields listed by [`MemoryMap::flatten`], in the same order
+    pub(super) fn leaves(&self) -> Vec<&Field> {
+        let mut fields = Vec::new();
+        self.field.leaves_into(&mut fields);
+        fields.sort_by_key(|field| field.address());
+        fields
+    }
+
+    /// Lists every field other than a set, ordered by address, with fields sharing an address
+    /// kept in declaration order. Must be called after [`MemoryMap::elaborate`]; fields without
+    /// an assigned address are omitted.
+    pub fn flatten(&self) -> Vec<FlatField> {
+        self.leaves()
+            .into_iter()
+            .filter_map(|field| {
+                Some(FlatField {
+                    path: field.path.clone(),
+                    address: field.address()?,
+                    bytes: field.size_bytes(&self.protocol),
+                    access: field.access?,
+                    range: field.range.clone(),
+                    offset: field.offset,
+                    bit_offset: field.bit_offset,
+                })
+            })
+            .collect()
+    }
+}
+
+impl MemoryMap {
+    /// Finds fields whose bits overlap a field at a lower address, or earlier in the same
+    /// packed unit. Explicit addresses may appear in any order, so overlaps are only known once
+    /// every field has been placed.
+    pub(super) fn check_overlaps(&self) -> Vec<ElaborationError> {
+        let mut errors = Vec::new();
+        // Last bit of the fields visited so far, counted from address zero
+        let mut end: Option<u128> = None;
+        for field in self.leaves() {
+            let Some(address) = field.address() else {
+                continue;
+            };
+            let bits = match field.bit_offset {
+                Some(_) => field.field_type.bit_length().unwrap_or(0),
+                None => field.size_bytes(&self.protocol) * 8,
+            };
+            if bits == 0 {
+                continue;
+            }
+            let first = u128::from(address) * 8 + u128::from(field.bit_offset.unwrap_or(0));
+            match end {
+                Some(end) if first <= end => errors.push(ElaborationError::Overlap {
+                    path: field.path.clone(),
+                    address,
+                    end: (end / 8) as u64,
+                }),
+                _ => (),
+            }
+            let last = first + u128::from(bits - 1);
+            end = Some(end.map_or(last, |end| end.max(last)));
+        }
+        errors
+    }
+}
@@ -0,0 +1,103 @@
+use super::svg::xml_escape;
+use super::{Field, FieldType, MemoryMap, hex_digits, render_to_string};
+use std::io::{self, Write};
+
+/// Style sheet embedded in the page so that it needs no other files
+const HTML_STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; }
+td { white-space: pre-line; }
+section { margin-top: 2em; }
+svg { max-width: 100%; height: auto; }";
+
+/// The `id` of a field's section: its path with runs of whitespace replaced by `-`
+pub fn html_anchor(path: &str) -> String {
+    path.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+impl Field {
+    /// Writes the section describing a leaf field, with a bit diagram if it is a bitfield
+    fn html_section<W: Write>(
+        &self,
+        map: &MemoryMap,
+        digits: usize,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let anchor = xml_escape(&html_anchor(&self.path));
+        let [address, access, reset, value] = self.doc_cells(digits);
+        writeln!(out, "<section id=\"{anchor}\">")?;
+        writeln!(out, "<h2>{}</h2>", xml_escape(&self.path))?;
+        writeln!(out, "<table>")?;
+        let rows = [
+            ("Address", address),
+            ("Type", self.field_type.to_string()),
+            ("Access", access),
+            ("Reset", reset),
+            ("Default", value),
+            ("Range", self.range.clone()),
+            ("Description", self.description.clone().unwrap_or_default()),
+        ];
+        for (heading, text) in rows.iter().filter(|(_, text)| !text.is_empty()) {
+            writeln!(
+                out,
+                "<tr><th>{heading}</th><td>{}</td></tr>",
+                xml_escape(text)
+            )?;
+        }
+        writeln!(out, "</table>")?;
+        if matches!(self.field_type, FieldType::Bitfield { .. }) {
+            let svg = self.to_svg_with_pattern(&map.protocol, &format!("reserved-{anchor}"));
+            out.write_all(svg.as_bytes())?;
+        }
+        writeln!(out, "</section>")
+    }
+}
+
+impl MemoryMap {
+    /// Renders a self-contained HTML5 page titled `title`. A table of contents links to a
+    /// section for every field other than a set, identified by [`html_anchor`] of its dotted
+    /// path, listing the details shown by [`MemoryMap::to_markdown`] along with the
+    /// [`Field::to_svg`] diagram of bitfields. Must be called after [`MemoryMap::elaborate`] so
+    /// that addresses, paths and ranges are populated.
+    pub fn to_html(&self, title: &str) -> String {
+        render_to_string(|out| self.write_html(title, out))
+    }
+
+    /// Streams the page rendered by [`MemoryMap::to_html`] to `out`
+    pub fn write_html<W: Write>(&self, title: &str, out: &mut W) -> io::Result<()> {
+        let title = xml_escape(title);
+        let digits = hex_digits(self.protocol.address_max);
+        let leaves = self.leaves();
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html lang=\"en\">")?;
+        writeln!(out, "<head>")?;
+        writeln!(out, "<meta charset=\"utf-8\">")?;
+        writeln!(out, "<title>{title}</title>")?;
+        writeln!(out, "<style>\n{HTML_STYLE}\n</style>")?;
+        writeln!(out, "</head>")?;
+        writeln!(out, "<body>")?;
+        writeln!(out, "<h1>{title}</h1>")?;
+        if let Some(description) = &self.field.description {
+            writeln!(out, "<p>{}</p>", xml_escape(description))?;
+        }
+        writeln!(out, "<nav>")?;
+        writeln!(out, "<h2>Contents</h2>")?;
+        writeln!(out, "<ul>")?;
+        for field in &leaves {
+            let [address, ..] = field.doc_cells(digits);
+            writeln!(
+                out,
+                "<li><a href=\"#{}\">{}</a> {address}</li>",
+                xml_escape(&html_anchor(&field.path)),
+                xml_escape(&field.path)
+            )?;
+        }
+        writeln!(out, "</ul>")?;
+        writeln!(out, "</nav>")?;
+        for field in leaves {
+            field.html_section(self, digits, out)?;
+        }
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")
+    }
+}
@@ -0,0 +1,296 @@
+use super::{
+    Access, Field, FieldType, MemoryMap, Protocol, Value, identifier, render_to_string,
+    sorted_variants,
+};
+use std::io::{self, Write};
+
+/// XML namespace of IEEE 1685-2014
+const IPXACT_NAMESPACE: &str = "http://www.accellera.org/XMLSchema/IPXACT/1685-2014";
+
+/// Escapes text for use as XML element content
+fn xml_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Writes a single-line `ipxact:` element indented by `depth` levels
+fn element<W: Write>(out: &mut W, depth: usize, tag: &str, text: &str) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:indent$}<ipxact:{tag}>{text}</ipxact:{tag}>",
+        "",
+        indent = depth * 2
+    )
+}
+
+/// Writes an `ipxact:` opening or closing tag indented by `depth` levels
+fn tag<W: Write>(out: &mut W, depth: usize, tag: &str) -> io::Result<()> {
+    writeln!(out, "{:indent$}<{tag}>", "", indent = depth * 2)
+}
+
+/// IP-XACT access value and, for side effects, the modified write value of an access
+fn ipxact_access(access: Access) -> (&'static str, Option<&'static str>) {
+    match access {
+        Access::Read => ("read-only", None),
+        Access::Write | Access::Strobe => ("write-only", None),
+        Access::ReadWrite => ("read-write", None),
+        Access::W1C => ("read-write", Some("oneToClear")),
+    }
+}
+
+impl Field {
+    /// Writes the name group: the identifier, the original name and any description
+    fn ipxact_names<W: Write>(&self, depth: usize, out: &mut W) -> io::Result<()> {
+        element(out, depth, "name", &identifier(&self.name))?;
+        element(out, depth, "displayName", &xml_text(&self.name))?;
+        if let Some(description) = &self.description {
+            element(out, depth, "description", &xml_text(description))?;
+        }
+        Ok(())
+    }
+
+    /// Reset value of the field as raw bits, when it has an integer interpretation
+    fn reset_bits(&self, bits: u64) -> Option<u128> {
+        let value = match (self.reset_value()?, &self.field_type) {
+            (Value::Unsigned(value), _) => i128::from(*value),
+            (Value::Signed(value), _) => i128::from(*value),
+            (Value::Prefixed(value, _), _) => *value,
+            (Value::Bool(value), _) => i128::from(*value),
+            (Value::String(name), FieldType::Enum { map, .. }) => i128::from(*map.get(name)?),
+            _ => return None,
+        };
+        // Negative values are written in two's complement
+        let mask = if bits >= 128 {
+            u128::MAX
+        } else {
+            (1 << bits) - 1
+        };
+        Some(value as u128 & mask)
+    }
+
+    /// Writes one `ipxact:field` element, listing the enumerated values of an enum
+    fn ipxact_field<W: Write>(
+        &self,
+        name: &str,
+        bit_offset: u64,
+        bit_width: u64,
+        reset: Option<u128>,
+        depth: usize,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let (access, modified_write) = ipxact_access(self.access.unwrap_or(Access::ReadWrite));
+        tag(out, depth, "ipxact:field")?;
+        element(out, depth + 1, "name", &identifier(name))?;
+        element(out, depth + 1, "bitOffset", &bit_offset.to_string())?;
+        if let Some(reset) = reset {
+            tag(out, depth + 1, "ipxact:resets")?;
+            tag(out, depth + 2, "ipxact:reset")?;
+            element(out, depth + 3, "value", &format!("'h{reset:X}"))?;
+            tag(out, depth + 2, "/ipxact:reset")?;
+            tag(out, depth + 1, "/ipxact:resets")?;
+        }
+        element(out, depth + 1, "bitWidth", &bit_width.to_string())?;
+        element(out, depth + 1, "access", access)?;
+        if let FieldType::Enum { map, .. } = &self.field_type {
+            tag(out, depth + 1, "ipxact:enumeratedValues")?;
+            for (value, name) in sorted_variants(map) {
+                tag(out, depth + 2, "ipxact:enumeratedValue")?;
+                element(out, depth + 3, "name", &identifier(name))?;
+                element(out, depth + 3, "value", &value.to_string())?;
+                tag(out, depth + 2, "/ipxact:enumeratedValue")?;
+            }
+            tag(out, depth + 1, "/ipxact:enumeratedValues")?;
+        }
+        if let Some(modified_write) = modified_write {
+            element(out, depth + 1, "modifiedWriteValue", modified_write)?;
+        }
+        tag(out, depth, "/ipxact:field")
+    }
+
+    /// Writes the fields of a register holding this field from bit `offset`. Named bitfield
+    /// bits become one field each; everything else is a single field spanning `bits`.
+    fn ipxact_fields<W: Write>(
+        &self,
+        bits: u64,
+        offset: u64,
+        depth: usize,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let reset = self.reset_bits(bits);
+        match &self.field_type {
+            FieldType::Bitfield {
+                length,
+                bits: named,
+            } if named.named_bits().range(..*length).next().is_some() => {
+                for (&index, name) in named.named_bits().range(..*length) {
+                    let bit_reset = reset.map(|reset| reset >> index & 1);
+                    self.ipxact_field(name, offset + index, 1, bit_reset, depth, out)?;
+                }
+                Ok(())
+            }
+            _ => self.ipxact_field(&self.name, offset, bits, reset, depth, out),
+        }
+    }
+
+    /// Writes the members of this set, offset from the enclosing block or register file at
+    /// `base`. Fields packed into the same Protocol.data_min unit by Field.packBits become the
+    /// fields of a single register.
+    fn ipxact_members<W: Write>(
+        &self,
+        base: u64,
+        protocol: &Protocol,
+        depth: usize,
+        out: &mut W,
+    ) -> io::Result<()> {
+        for group in self.member_groups() {
+            match group {
+                [field, ..] if field.bit_offset.is_some() => {
+                    Field::ipxact_packed(group, base, protocol, depth, out)?
+                }
+                _ => group[0].ipxact_item(base, protocol, depth, out)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the fields packed into one Protocol.data_min unit as a single register named
+    /// after all of them
+    fn ipxact_packed<W: Write>(
+        group: &[Field],
+        base: u64,
+        protocol: &Protocol,
+        depth: usize,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let Some(address) = group[0].address() else {
+            return Ok(());
+        };
+        tag(out, depth, "ipxact:register")?;
+        if let [field] = group {
+            field.ipxact_names(depth + 1, out)?;
+        } else {
+            let names: Vec<&str> = group.iter().map(|field| field.name.as_str()).collect();
+            element(out, depth + 1, "name", &identifier(&names.join(" ")))?;
+            element(out, depth + 1, "displayName", &xml_text(&names.join(", ")))?;
+        }
+        let offset = format!("'h{:X}", address.saturating_sub(base));
+        element(out, depth + 1, "addressOffset", &offset)?;
+        let size = u64::from(protocol.data_min) * 8;
+        element(out, depth + 1, "size", &size.to_string())?;
+        for field in group {
+            if let Some((low, length)) = field.packed_bits() {
+                field.ipxact_fields(length, low, depth + 1, out)?;
+            }
+        }
+        tag(out, depth, "/ipxact:register")
+    }
+
+    /// Writes this field as an `ipxact:registerFile` if it is a set, otherwise as an
+    /// `ipxact:register`, offset from the enclosing block or register file at `base`
+    fn ipxact_item<W: Write>(
+        &self,
+        base: u64,
+        protocol: &Protocol,
+        depth: usize,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let Some(address) = self.address() else {
+            return Ok(());
+        };
+        if matches!(self.field_type, FieldType::Reserved(_)) {
+            return Ok(());
+        }
+        let offset = format!("'h{:X}", address.saturating_sub(base));
+        if matches!(self.field_type, FieldType::Set) {
+            tag(out, depth, "ipxact:registerFile")?;
+            self.ipxact_names(depth + 1, out)?;
+            element(out, depth + 1, "addressOffset", &offset)?;
+            element(
+                out,
+                depth + 1,
+                "range",
+                &self.size_bytes(protocol).to_string(),
+            )?;
+            self.ipxact_members(address, protocol, depth + 1, out)?;
+            return tag(out, depth, "/ipxact:registerFile");
+        }
+        tag(out, depth, "ipxact:register")?;
+        self.ipxact_names(depth + 1, out)?;
+        let data_min = u64::from(protocol.data_min);
+        // Arrays of evenly packed registers are described by a dimension, which IP-XACT 2014
+        // can only express when the stride equals the element width
+        let element_bytes = match &self.field_type {
+            FieldType::Array {
+                count,
+                stride,
+                element: element_type,
+            } if !matches!(**element_type, FieldType::Array { .. }) => {
+                let width = element_type.byte_width(data_min);
+                match width {
+                    Some(width) if stride.unwrap_or(width.next_multiple_of(data_min)) == width => {
+                        element(out, depth + 1, "dim", &count.to_string())?;
+                        Some(width)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        element(out, depth + 1, "addressOffset", &offset)?;
+        let bytes = element_bytes.unwrap_or_else(|| self.size_bytes(protocol));
+        element(out, depth + 1, "size", &(bytes * 8).to_string())?;
+        let bits = match element_bytes {
+            Some(_) => bytes * 8,
+            None => self.field_type.bit_length().unwrap_or(bytes * 8),
+        };
+        self.ipxact_fields(bits, 0, depth + 1, out)?;
+        tag(out, depth, "/ipxact:register")
+    }
+}
+
+impl MemoryMap {
+    /// Renders an IP-XACT (IEEE 1685-2014) component named `component_name` with a single
+    /// memory map. Sets become register files and every other field a register whose fields
+    /// carry the bit offset, width, reset value and access; named bitfield bits are fields of
+    /// their own, fields packed by Field.packBits share a register and enums list their values.
+    /// Must be called after [`MemoryMap::elaborate`] so that addresses are populated.
+    pub fn to_ipxact(&self, component_name: &str) -> String {
+        render_to_string(|out| self.write_ipxact(component_name, out))
+    }
+
+    /// Streams the component rendered by [`MemoryMap::to_ipxact`] to `out`
+    pub fn write_ipxact<W: Write>(&self, component_name: &str, out: &mut W) -> io::Result<()> {
+        let name = identifier(&self.field.name);
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            out,
+            "<ipxact:component xmlns:ipxact=\"{IPXACT_NAMESPACE}\">"
+        )?;
+        element(out, 1, "vendor", "vhdl_doc")?;
+        element(out, 1, "library", "vhdl_doc")?;
+        element(out, 1, "name", &xml_text(component_name))?;
+        element(out, 1, "version", "1.0")?;
+        tag(out, 1, "ipxact:memoryMaps")?;
+        tag(out, 2, "ipxact:memoryMap")?;
+        element(out, 3, "name", &name)?;
+        tag(out, 3, "ipxact:addressBlock")?;
+        self.field.ipxact_names(4, out)?;
+        let base = self.protocol.base_address.unwrap_or(0);
+        element(out, 4, "baseAddress", &format!("'h{base:X}"))?;
+        let range = (u128::from(self.protocol.address_max) + 1).saturating_sub(u128::from(base));
+        element(out, 4, "range", &range.to_string())?;
+        let width = self.protocol.data_max.unwrap_or(self.protocol.data_min);
+        element(out, 4, "width", &(u32::from(width) * 8).to_string())?;
+        if matches!(self.field.field_type, FieldType::Set) {
+            self.field.ipxact_members(base, &self.protocol, 4, out)?;
+        } else {
+            self.field.ipxact_item(base, &self.protocol, 4, out)?;
+        }
+        tag(out, 3, "/ipxact:addressBlock")?;
+        element(out, 3, "addressUnitBits", "8")?;
+        tag(out, 2, "/ipxact:memoryMap")?;
+        tag(out, 1, "/ipxact:memoryMaps")?;
+        writeln!(out, "</ipxact:component>")
+    }
+}
@@ -0,0 +1,92 @@
+use super::{Field, FieldType, MemoryMap, hex_digits, render_to_string};
+use std::io::{self, Write};
+
+/// Escapes text for use inside a Markdown table cell
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+impl Field {
+    /// The address, access, reset value and default value of the field as shown in the
+    /// documentation, each empty when not specified. The address of a packed field is followed
+    /// by the bits it occupies, as in `0x04[5:3]`.
+    pub(super) fn doc_cells(&self, digits: usize) -> [String; 4] {
+        let bits = match (self.bit_offset, self.field_type.bit_length()) {
+            (Some(low), Some(1)) => format!("[{low}]"),
+            (Some(low), Some(length)) if length > 1 => format!("[{}:{low}]", low + length - 1),
+            _ => String::new(),
+        };
+        let address = self
+            .address()
+            .map(|address| format!("0x{address:0digits$X}{bits}"))
+            .unwrap_or_default();
+        let access = self
+            .access
+            .map(|access| access.to_string())
+            .unwrap_or_default();
+        let reset = self
+            .reset_value()
+            .map(|reset| reset.to_string())
+            .unwrap_or_default();
+        let value = self
+            .value
+            .as_ref()
+            .map(|value| value.to_string())
+            .unwrap_or_default();
+        [address, access, reset, value]
+    }
+
+    /// Writes a table row for this field followed by rows for any fields it contains
+    fn markdown_rows<W: Write>(&self, path: &str, digits: usize, out: &mut W) -> io::Result<()> {
+        let [address, access, reset, value] = self.doc_cells(digits);
+        let description = self.description.as_deref().unwrap_or_default();
+        writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {} | {} | {} |",
+            address,
+            cell(path),
+            self.field_type,
+            access,
+            cell(&reset),
+            cell(&value),
+            cell(&self.range),
+            cell(description)
+        )?;
+        for child in self.children() {
+            child.markdown_rows(&format!("{path}.{}", child.name), digits, out)?;
+        }
+        Ok(())
+    }
+}
+
+impl MemoryMap {
+    /// Renders the memory map as a Markdown register table, naming nested set members by their
+    /// dotted path below the top-level set. Must be called after [`MemoryMap::elaborate`] so that
+    /// addresses, access and ranges are populated.
+    pub fn to_markdown(&self) -> String {
+        render_to_string(|out| self.write_markdown(out))
+    }
+
+    /// Streams the table rendered by [`MemoryMap::to_markdown`] to `out`
+    pub fn write_markdown<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let digits = hex_digits(self.protocol.address_max);
+        writeln!(out, "# {}\n", self.field.name)?;
+        if let Some(description) = &self.field.description {
+            writeln!(out, "{description}\n")?;
+        }
+        out.write_all(
+            b"| Address | Name | Type | Access | Reset | Default | Range | Description |\n",
+        )?;
+        out.write_all(
+            b"| ------- | ---- | ---- | ------ | ----- | ------- | ----- | ----------- |\n",
+        )?;
+        if matches!(self.field.field_type, FieldType::Set) {
+            for child in self.field.children() {
+                child.markdown_rows(&child.name, digits, out)?;
+            }
+        } else {
+            self.field.markdown_rows(&self.field.name, digits, out)?;
+        }
+        Ok(())
+    }
+}
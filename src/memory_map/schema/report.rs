@@ -0,0 +1,116 @@
+use super::{Field, MemoryMap};
+use serde::Serialize;
+use std::fmt;
+
+/// An inclusive range of byte addresses
+#[derive(Debug, PartialEq, Serialize)]
+pub struct AddressRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl AddressRange {
+    /// Number of bytes in the range
+    pub fn bytes(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// Address space usage of an elaborated memory map
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressReport {
+    /// Unused byte ranges between address zero and the highest used address
+    pub gaps: Vec<AddressRange>,
+    /// Bytes occupied by fields, counting overlapping fields once
+    pub used_bytes: u64,
+    /// Bytes up to and including Protocol.address_max that no field occupies
+    pub free_bytes: u64,
+}
+
+impl fmt::Display for AddressReport {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            fmt,
+            "{} bytes used, {} bytes free",
+            self.used_bytes, self.free_bytes
+        )?;
+        if self.gaps.is_empty() {
+            return writeln!(fmt, "No gaps");
+        }
+        writeln!(fmt, "Gaps:")?;
+        for gap in &self.gaps {
+            writeln!(
+                fmt,
+                "  0x{:x} .. 0x{:x} ({} bytes)",
+                gap.start,
+                gap.end,
+                gap.bytes()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Field {
+    /// Appends the byte ranges occupied by this field, or by the fields within a set
+    fn occupied(&self, data_min: u64, out: &mut Vec<AddressRange>) {
+        let Some(address) = self.address() else {
+            return;
+        };
+        match self.field_type.byte_width(data_min) {
+            Some(0) => (),
+            Some(bytes) => out.push(AddressRange {
+                start: address,
+                end: address + (bytes - 1),
+            }),
+            None => {
+                for child in self.children() {
+                    child.occupied(data_min, out);
+                }
+            }
+        }
+    }
+}
+
+impl MemoryMap {
+    /// Reports the unused ranges between Protocol.base_address, or zero, and the highest used
+    /// address, and the used and free byte totals of the space from there to
+    /// Protocol.address_max. Must be called after
+    /// [`MemoryMap::elaborate`] so that addresses are populated.
+    pub fn address_report(&self) -> AddressReport {
+        let mut occupied = Vec::new();
+        self.field
+            .occupied(u64::from(self.protocol.data_min), &mut occupied);
+        occupied.sort_by_key(|range| range.start);
+        let mut gaps = Vec::new();
+        let mut used_bytes = 0;
+        // First address not yet occupied, or None once the top of the address space is
+        let base = self.protocol.base_address.unwrap_or(0);
+        let mut next = Some(base);
+        for range in occupied {
+            let Some(start) = next else {
+                break;
+            };
+            if range.end < start {
+                continue;
+            }
+            if range.start > start {
+                gaps.push(AddressRange {
+                    start,
+                    end: range.start - 1,
+                });
+            }
+            used_bytes += range.end - range.start.max(start) + 1;
+            next = range.end.checked_add(1);
+        }
+        let capacity = (u128::from(self.protocol.address_max) + 1).saturating_sub(u128::from(base));
+        let free_bytes =
+            (capacity.saturating_sub(u128::from(used_bytes))).min(u128::from(u64::MAX));
+        AddressReport {
+            gaps,
+            used_bytes,
+            free_bytes: free_bytes as u64,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use super::{Field, FieldType, Protocol};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const CELL_WIDTH: u64 = 32;
+const CELL_HEIGHT: u64 = 40;
+const INDEX_HEIGHT: u64 = 16;
+const MARGIN: u64 = 4;
+
+/// Escapes text for use in SVG character data and attribute values
+pub(super) fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Field {
+    /// Renders the bit layout of a bitfield, enum, boolean or numeric field as a standalone SVG
+    /// diagram: a row of bit cells, most significant bit first, with bit indices above and the
+    /// byte offset of each group of eight bits below, following Protocol.endianness. Bitfield
+    /// cells are labeled with their bit names while other types carry the field name across all
+    /// of their bits. Unnamed bits and any padding up to Protocol.data_min are hatched as
+    /// reserved. Returns an empty string for types without a bit layout.
+    pub fn to_svg(&self, protocol: &Protocol) -> String {
+        self.to_svg_with_pattern(protocol, "reserved")
+    }
+
+    /// Renders the diagram of [`Field::to_svg`] with the hatching pattern of reserved bits
+    /// defined under the id `pattern_id`, which must be unique among diagrams embedded in the
+    /// same document.
+    pub fn to_svg_with_pattern(&self, protocol: &Protocol, pattern_id: &str) -> String {
+        let Some(length) = self.field_type.bit_length() else {
+            return String::new();
+        };
+        let (low, names): (i64, BTreeMap<u64, &str>) = match &self.field_type {
+            FieldType::Bitfield { bits, .. } => (0, bits.named_bits()),
+            FieldType::UFixed { low, .. } | FieldType::SFixed { low, .. } => {
+                (*low, BTreeMap::new())
+            }
+            _ => (0, BTreeMap::new()),
+        };
+        let is_bitfield = matches!(self.field_type, FieldType::Bitfield { .. });
+        let bits = length
+            .max(1)
+            .next_multiple_of(u64::from(protocol.data_min.max(1)) * 8);
+        let width = 2 * MARGIN + bits * CELL_WIDTH;
+        let height = 2 * MARGIN + 2 * INDEX_HEIGHT + CELL_HEIGHT;
+        let cell_y = MARGIN + INDEX_HEIGHT;
+        let text_y = cell_y + CELL_HEIGHT / 2;
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="monospace" font-size="10" text-anchor="middle" dominant-baseline="middle">"#
+        )
+        .unwrap();
+        writeln!(out, "<title>{}</title>", xml_escape(&self.name)).unwrap();
+        let pattern_id = xml_escape(pattern_id);
+        writeln!(
+            out,
+            r##"<defs><pattern id="{pattern_id}" patternUnits="userSpaceOnUse" width="6" height="6" patternTransform="rotate(45)"><line x1="0" y1="0" x2="0" y2="6" stroke="#999" stroke-width="2"/></pattern></defs>"##
+        )
+        .unwrap();
+        for bit in (0..bits).rev() {
+            let x = MARGIN + (bits - 1 - bit) * CELL_WIDTH;
+            let center = x + CELL_WIDTH / 2;
+            let reserved = bit >= length || (is_bitfield && !names.contains_key(&bit));
+            let fill = if reserved {
+                format!("url(#{pattern_id})")
+            } else {
+                "white".to_string()
+            };
+            writeln!(
+                out,
+                r#"<text x="{center}" y="{}">{}</text>"#,
+                MARGIN + INDEX_HEIGHT / 2,
+                bit as i64 + low
+            )
+            .unwrap();
+            writeln!(
+                out,
+                r#"<rect x="{x}" y="{cell_y}" width="{CELL_WIDTH}" height="{CELL_HEIGHT}" fill="{fill}" stroke="black"/>"#
+            )
+            .unwrap();
+            if let Some(name) = names.get(&bit).filter(|_| bit < length) {
+                writeln!(
+                    out,
+                    r#"<text x="{center}" y="{text_y}">{}</text>"#,
+                    xml_escape(name)
+                )
+                .unwrap();
+            }
+        }
+        if !is_bitfield && length > 0 {
+            let center = MARGIN + (bits - length) * CELL_WIDTH + length * CELL_WIDTH / 2;
+            writeln!(
+                out,
+                r#"<text x="{center}" y="{text_y}">{}</text>"#,
+                xml_escape(&self.name)
+            )
+            .unwrap();
+        }
+        let lane_y = cell_y + CELL_HEIGHT + INDEX_HEIGHT / 2;
+        for lane in self.byte_lanes(protocol).iter().rev() {
+            let center = MARGIN + (bits - 1 - lane.high) * CELL_WIDTH + 4 * CELL_WIDTH;
+            writeln!(
+                out,
+                r#"<text x="{center}" y="{lane_y}">+{}</text>"#,
+                lane.offset
+            )
+            .unwrap();
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
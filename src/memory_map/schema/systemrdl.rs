@@ -0,0 +1,917 @@
+use super::{
+    Access, Address, BitfieldStyle, Endianness, Field, FieldType, MemoryMap, OneOrMoreField,
+    Protocol, Value,
+};
+use anyhow::{anyhow, bail};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Width in bits of a `reg` that does not set `regwidth`
+const DEFAULT_REGWIDTH: u64 = 32;
+
+/// Widest `regwidth` imported, matching the widest integer a register reset is read into
+const MAX_REGWIDTH: u64 = 64;
+
+/// SystemRDL keywords introducing constructs that are not imported
+const UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "alias",
+    "constraint",
+    "default",
+    "enum",
+    "external",
+    "internal",
+    "mem",
+    "property",
+    "signal",
+    "struct",
+];
+
+/// A lexical token of SystemRDL source
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(u64),
+    Str(String),
+    Punct(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(fmt, "`{ident}`"),
+            Token::Number(number) => write!(fmt, "`{number}`"),
+            Token::Str(text) => write!(fmt, "\"{text}\""),
+            Token::Punct(c) => write!(fmt, "`{c}`"),
+        }
+    }
+}
+
+/// Parses a decimal, `0x` hexadecimal or Verilog-style (`8'h1F`) integer literal
+fn parse_number(literal: &str) -> Option<u64> {
+    let literal = literal.replace('_', "");
+    if let Some((_, value)) = literal.split_once('\'') {
+        let mut chars = value.chars();
+        let radix = match chars.next()?.to_ascii_lowercase() {
+            'b' => 2,
+            'o' => 8,
+            'd' => 10,
+            'h' => 16,
+            _ => return None,
+        };
+        u64::from_str_radix(chars.as_str(), radix).ok()
+    } else if let Some(hex) = literal
+        .strip_prefix("0x")
+        .or_else(|| literal.strip_prefix("0X"))
+    {
+        u64::from_str_radix(hex, 16).ok()
+    } else {
+        literal.parse().ok()
+    }
+}
+
+/// Splits SystemRDL source into tokens tagged with their line number, dropping comments
+fn tokenize(src: &str) -> Result<Vec<(Token, usize)>, anyhow::Error> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '/' if chars.next_if_eq(&'/').is_some() => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+            }
+            '/' if chars.next_if_eq(&'*').is_some() => {
+                let start = line;
+                let mut previous = ' ';
+                loop {
+                    let Some(c) = chars.next() else {
+                        bail!("Unterminated comment starting at line {start}");
+                    };
+                    if c == '\n' {
+                        line += 1;
+                    } else if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '"' => {
+                let start = line;
+                let mut text = String::new();
+                loop {
+                    let c = match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => chars.next(),
+                        c => c,
+                    };
+                    let Some(c) = c else {
+                        bail!("Unterminated string starting at line {start}");
+                    };
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    text.push(c);
+                }
+                tokens.push((Token::Str(text), start));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut ident = String::from(c);
+                while let Some(c) = chars.next_if(|&c| c.is_ascii_alphanumeric() || c == '_') {
+                    ident.push(c);
+                }
+                tokens.push((Token::Ident(ident), line));
+            }
+            c if c.is_ascii_digit() || c == '\'' => {
+                let mut literal = String::from(c);
+                while let Some(c) =
+                    chars.next_if(|&c| c.is_ascii_alphanumeric() || c == '_' || c == '\'')
+                {
+                    literal.push(c);
+                }
+                let number = parse_number(&literal)
+                    .ok_or_else(|| anyhow!("Invalid number `{literal}` at line {line}"))?;
+                tokens.push((Token::Number(number), line));
+            }
+            '{' | '}' | '[' | ']' | ';' | '=' | '@' | ':' | ',' => {
+                tokens.push((Token::Punct(c), line));
+            }
+            '-' | '+' | '%' => bail!(
+                "Unsupported operator at line {line}; dynamic assignments (`->`), strides (`+=`) \
+                 and alignments (`%=`) are not imported"
+            ),
+            _ => bail!("Unexpected character `{c}` at line {line}"),
+        }
+    }
+    Ok(tokens)
+}
+
+/// The component types that can be imported
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Addrmap,
+    Regfile,
+    Reg,
+    Field,
+}
+
+impl Kind {
+    fn from_keyword(keyword: &str) -> Option<Kind> {
+        match keyword {
+            "addrmap" => Some(Kind::Addrmap),
+            "regfile" => Some(Kind::Regfile),
+            "reg" => Some(Kind::Reg),
+            "field" => Some(Kind::Field),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(match self {
+            Kind::Addrmap => "addrmap",
+            Kind::Regfile => "regfile",
+            Kind::Reg => "reg",
+            Kind::Field => "field",
+        })
+    }
+}
+
+/// Right-hand side of a property assignment
+#[derive(Clone, Debug)]
+enum PropertyValue {
+    Bool(bool),
+    Number(u64),
+    Str(String),
+    Ident(String),
+}
+
+#[derive(Clone, Debug)]
+struct Property {
+    name: String,
+    value: PropertyValue,
+    line: usize,
+}
+
+/// Bit selection following a field instance name
+#[derive(Clone, Copy, Debug)]
+enum Bits {
+    /// `[msb:lsb]`
+    Range(u64, u64),
+    /// `[width]`, placed after the previous field
+    Width(u64),
+}
+
+/// The body of a component definition
+#[derive(Clone, Debug)]
+struct Component {
+    kind: Kind,
+    properties: Vec<Property>,
+    instances: Vec<Instance>,
+}
+
+/// A named instance of a component
+#[derive(Clone, Debug)]
+struct Instance {
+    name: String,
+    component: Component,
+    bits: Option<Bits>,
+    reset: Option<u64>,
+    address: Option<u64>,
+    line: usize,
+}
+
+/// Recursive descent parser building the component tree
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    /// Named component definitions visible in each enclosing scope
+    scopes: Vec<HashMap<String, Component>>,
+    /// The most recent addrmap defined or instantiated at the top level
+    root: Option<Instance>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    /// Line of the next token, or of the last one at the end of the source
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn next(&mut self) -> Result<Token, anyhow::Error> {
+        let (token, _) = self
+            .tokens
+            .get(self.pos)
+            .ok_or_else(|| anyhow!("Unexpected end of SystemRDL source"))?;
+        self.pos += 1;
+        Ok(token.clone())
+    }
+
+    /// Consumes the punctuation `c` if it is next
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(&Token::Punct(c));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), anyhow::Error> {
+        let line = self.line();
+        match self.next()? {
+            Token::Punct(found) if found == c => Ok(()),
+            token => bail!("Expected `{c}` at line {line}, found {token}"),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, anyhow::Error> {
+        let line = self.line();
+        match self.next()? {
+            Token::Ident(ident) => Ok(ident),
+            token => bail!("Expected an identifier at line {line}, found {token}"),
+        }
+    }
+
+    fn number(&mut self) -> Result<u64, anyhow::Error> {
+        let line = self.line();
+        match self.next()? {
+            Token::Number(number) => Ok(number),
+            token => bail!("Expected a number at line {line}, found {token}"),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Component> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// Parses the statements of a component body up to and including its closing brace
+    fn component(&mut self, kind: Kind) -> Result<Component, anyhow::Error> {
+        let line = self.line();
+        let mut component = Component {
+            kind,
+            properties: Vec::new(),
+            instances: Vec::new(),
+        };
+        self.scopes.push(HashMap::new());
+        while !self.eat('}') {
+            if self.peek().is_none() {
+                bail!("Unterminated {kind} body starting at line {line}");
+            }
+            self.statement(&mut component)?;
+        }
+        self.scopes.pop();
+        Ok(component)
+    }
+
+    /// Parses one definition, instantiation or property assignment into `parent`
+    fn statement(&mut self, parent: &mut Component) -> Result<(), anyhow::Error> {
+        let line = self.line();
+        let word = self.ident()?;
+        if let Some(kind) = Kind::from_keyword(&word) {
+            let name = match self.peek() {
+                Some(Token::Ident(_)) => Some(self.ident()?),
+                _ => None,
+            };
+            self.expect('{')?;
+            let component = self.component(kind)?;
+            if let Some(name) = &name {
+                self.scopes
+                    .last_mut()
+                    .expect("scope stack is never empty")
+                    .insert(name.clone(), component.clone());
+            }
+            if self.eat(';') {
+                let Some(name) = name else {
+                    bail!("Anonymous {kind} at line {line} is never instantiated");
+                };
+                if kind == Kind::Addrmap && self.scopes.len() == 1 {
+                    self.root = Some(Instance {
+                        name,
+                        component,
+                        bits: None,
+                        reset: None,
+                        address: None,
+                        line,
+                    });
+                }
+                Ok(())
+            } else {
+                self.instances(component, parent)
+            }
+        } else if UNSUPPORTED_KEYWORDS.contains(&word.as_str()) {
+            bail!("Unsupported SystemRDL construct `{word}` at line {line}")
+        } else if self.eat('=') {
+            let value = match self.next()? {
+                Token::Number(number) => PropertyValue::Number(number),
+                Token::Str(text) => PropertyValue::Str(text),
+                Token::Ident(ident) => match ident.as_str() {
+                    "true" => PropertyValue::Bool(true),
+                    "false" => PropertyValue::Bool(false),
+                    _ => PropertyValue::Ident(ident),
+                },
+                token => bail!("Unexpected {token} assigned to `{word}` at line {line}"),
+            };
+            self.expect(';')?;
+            parent.properties.push(Property {
+                name: word,
+                value,
+                line,
+            });
+            Ok(())
+        } else if self.eat(';') {
+            parent.properties.push(Property {
+                name: word,
+                value: PropertyValue::Bool(true),
+                line,
+            });
+            Ok(())
+        } else {
+            let component = self
+                .lookup(&word)
+                .ok_or_else(|| anyhow!("Unknown component type `{word}` at line {line}"))?
+                .clone();
+            self.instances(component, parent)
+        }
+    }
+
+    /// Parses the comma separated instances of `component` ending a definition or an
+    /// instantiation of a named definition
+    fn instances(
+        &mut self,
+        component: Component,
+        parent: &mut Component,
+    ) -> Result<(), anyhow::Error> {
+        loop {
+            let line = self.line();
+            let name = self.ident()?;
+            let bits = if self.eat('[') {
+                let first = self.number()?;
+                let bits = if self.eat(':') {
+                    Bits::Range(first, self.number()?)
+                } else {
+                    Bits::Width(first)
+                };
+                self.expect(']')?;
+                Some(bits)
+            } else {
+                None
+            };
+            let reset = if self.eat('=') {
+                Some(self.number()?)
+            } else {
+                None
+            };
+            let address = if self.eat('@') {
+                Some(self.number()?)
+            } else {
+                None
+            };
+            let instance = Instance {
+                name,
+                component: component.clone(),
+                bits,
+                reset,
+                address,
+                line,
+            };
+            if self.scopes.len() > 1 {
+                parent.instances.push(instance);
+            } else if component.kind == Kind::Addrmap {
+                self.root = Some(instance);
+            } else {
+                bail!(
+                    "Only an addrmap can be instantiated at the top level, found {} {} at line {line}",
+                    component.kind,
+                    instance.name
+                );
+            }
+            if !self.eat(',') {
+                return self.expect(';');
+            }
+        }
+    }
+}
+
+impl Component {
+    /// The last assignment of the property `name`
+    fn property(&self, name: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .rev()
+            .find(|property| property.name == name)
+    }
+
+    /// Rejects properties other than `allowed`, which would otherwise be silently dropped
+    fn check_properties(&self, instance: &str, allowed: &[&str]) -> Result<(), anyhow::Error> {
+        match self
+            .properties
+            .iter()
+            .find(|property| !allowed.contains(&property.name.as_str()))
+        {
+            Some(property) => bail!(
+                "Unsupported property `{}` of {} {instance} at line {}",
+                property.name,
+                self.kind,
+                property.line
+            ),
+            None => Ok(()),
+        }
+    }
+
+    fn text(&self, name: &str) -> Result<Option<String>, anyhow::Error> {
+        match self.property(name) {
+            None => Ok(None),
+            Some(Property {
+                value: PropertyValue::Str(text),
+                ..
+            }) => Ok(Some(text.clone())),
+            Some(property) => bail!(
+                "Property `{name}` at line {} must be a string",
+                property.line
+            ),
+        }
+    }
+
+    fn number(&self, name: &str) -> Result<Option<u64>, anyhow::Error> {
+        match self.property(name) {
+            None => Ok(None),
+            Some(Property {
+                value: PropertyValue::Number(number),
+                ..
+            }) => Ok(Some(*number)),
+            Some(property) => bail!(
+                "Property `{name}` at line {} must be a number",
+                property.line
+            ),
+        }
+    }
+
+    fn keyword(&self, name: &str) -> Result<Option<&str>, anyhow::Error> {
+        match self.property(name) {
+            None => Ok(None),
+            Some(Property {
+                value: PropertyValue::Ident(ident),
+                ..
+            }) => Ok(Some(ident)),
+            Some(property) => bail!(
+                "Property `{name}` at line {} must be a keyword",
+                property.line
+            ),
+        }
+    }
+
+    fn flag(&self, name: &str) -> Result<bool, anyhow::Error> {
+        match self.property(name) {
+            None => Ok(false),
+            Some(Property {
+                value: PropertyValue::Bool(flag),
+                ..
+            }) => Ok(*flag),
+            Some(property) => bail!(
+                "Property `{name}` at line {} must be a boolean",
+                property.line
+            ),
+        }
+    }
+
+    /// The `desc` property, falling back to the human readable `name`
+    fn description(&self) -> Result<Option<String>, anyhow::Error> {
+        Ok(self.text("desc")?.or(self.text("name")?))
+    }
+
+    /// Software access of a field, from its `sw`, `onwrite` and `singlepulse` properties. The
+    /// `hw` property describes the hardware side, which a memory map does not document.
+    fn field_access(&self, instance: &Instance) -> Result<Access, anyhow::Error> {
+        let name = &instance.name;
+        let line = instance.line;
+        let mut access = match self.keyword("sw")?.unwrap_or("rw") {
+            "rw" | "wr" => Access::ReadWrite,
+            "r" => Access::Read,
+            "w" => Access::Write,
+            sw => bail!("Unsupported sw access `{sw}` of field {name} at line {line}"),
+        };
+        match self.keyword("hw")? {
+            None | Some("r" | "w" | "rw" | "wr" | "na") => {}
+            Some(hw) => bail!("Unsupported hw access `{hw}` of field {name} at line {line}"),
+        }
+        match self.keyword("onwrite")? {
+            None => {}
+            Some("woclr") if access == Access::ReadWrite => access = Access::W1C,
+            Some(onwrite) => bail!(
+                "Unsupported onwrite `{onwrite}` of field {name} at line {line}; only woclr on a \
+                 sw = rw field is imported"
+            ),
+        }
+        if self.flag("singlepulse")? {
+            if access == Access::Read {
+                bail!("Read-only field {name} at line {line} cannot be a single pulse");
+            }
+            access = Access::Strobe;
+        }
+        Ok(access)
+    }
+}
+
+/// A field of a register, positioned within it
+struct RegisterField<'a> {
+    name: &'a str,
+    lsb: u64,
+    width: u64,
+    access: Access,
+    reset: Option<u64>,
+    description: Option<String>,
+}
+
+impl Instance {
+    /// Rejects array dimensions and reset values on anything but a field
+    fn check_not_field(&self) -> Result<(), anyhow::Error> {
+        if self.bits.is_some() {
+            bail!(
+                "Arrays of {} such as {} at line {} are not supported",
+                self.component.kind,
+                self.name,
+                self.line
+            );
+        }
+        if self.reset.is_some() {
+            bail!(
+                "Only fields can have a reset value, found {} {} at line {}",
+                self.component.kind,
+                self.name,
+                self.line
+            );
+        }
+        Ok(())
+    }
+
+    /// Width in bits of a register, which must be a power of two number of bytes
+    fn regwidth(&self) -> Result<u64, anyhow::Error> {
+        let regwidth = self
+            .component
+            .number("regwidth")?
+            .unwrap_or(DEFAULT_REGWIDTH);
+        if !(8..=MAX_REGWIDTH).contains(&regwidth) || !regwidth.is_power_of_two() {
+            bail!(
+                "Register {} at line {} has regwidth {regwidth}; expected a power of two from 8 to {MAX_REGWIDTH}",
+                self.name,
+                self.line
+            );
+        }
+        Ok(regwidth)
+    }
+
+    /// Positions the fields of a register, packing those without an explicit `[msb:lsb]`
+    /// after the previous field as SystemRDL does for lsb0 registers
+    fn register_fields(&self, regwidth: u64) -> Result<Vec<RegisterField<'_>>, anyhow::Error> {
+        let mut fields = Vec::new();
+        let mut next = 0;
+        for child in &self.component.instances {
+            let component = &child.component;
+            let name = &child.name;
+            let line = child.line;
+            if component.kind != Kind::Field {
+                bail!(
+                    "{} {name} at line {line} cannot be placed in reg {}",
+                    component.kind,
+                    self.name
+                );
+            }
+            if child.address.is_some() {
+                bail!("Field {name} at line {line} cannot have an address");
+            }
+            component.check_properties(
+                name,
+                &[
+                    "name",
+                    "desc",
+                    "sw",
+                    "hw",
+                    "reset",
+                    "onwrite",
+                    "singlepulse",
+                    "fieldwidth",
+                ],
+            )?;
+            let (lsb, width) = match child.bits {
+                Some(Bits::Range(msb, lsb)) if msb >= lsb => (lsb, (msb - lsb).checked_add(1)),
+                Some(Bits::Range(..)) => {
+                    bail!(
+                        "Field {name} at line {line} uses msb0 bit ordering, which is not supported"
+                    )
+                }
+                Some(Bits::Width(width)) => (next, Some(width)),
+                None => (next, Some(component.number("fieldwidth")?.unwrap_or(1))),
+            };
+            let Some(width) = width.filter(|&width| {
+                width > 0 && lsb.checked_add(width).is_some_and(|end| end <= regwidth)
+            }) else {
+                bail!(
+                    "Field {name} at line {line} does not fit in the {regwidth}-bit register {}",
+                    self.name
+                );
+            };
+            next = lsb + width;
+            let reset = match child.reset {
+                Some(reset) => Some(reset),
+                None => component.number("reset")?,
+            };
+            if let Some(reset) = reset
+                && width < 64
+                && reset >> width != 0
+            {
+                bail!(
+                    "Reset value {reset} of field {name} at line {line} does not fit in {width} bits"
+                );
+            }
+            fields.push(RegisterField {
+                name,
+                lsb,
+                width,
+                access: component.field_access(child)?,
+                reset,
+                description: component.description()?,
+            });
+        }
+        fields.sort_by_key(|field| field.lsb);
+        if let Some(pair) = fields
+            .windows(2)
+            .find(|pair| pair[0].lsb + pair[0].width > pair[1].lsb)
+        {
+            bail!(
+                "Fields {} and {} of register {} overlap",
+                pair[0].name,
+                pair[1].name,
+                self.name
+            );
+        }
+        Ok(fields)
+    }
+
+    /// Converts a register at `address`. A register holding one field from bit 0 becomes a
+    /// boolean or unsigned field, one holding only single-bit fields a bitfield, and one
+    /// holding byte aligned fields a set of unsigned fields.
+    fn register(&self, address: u64) -> Result<Field, anyhow::Error> {
+        self.check_not_field()?;
+        self.component
+            .check_properties(&self.name, &["name", "desc", "regwidth", "accesswidth"])?;
+        let regwidth = self.regwidth()?;
+        let fields = self.register_fields(regwidth)?;
+        let description = self.component.description()?;
+        match fields.as_slice() {
+            [] => bail!("Register {} at line {} has no fields", self.name, self.line),
+            [field] if field.lsb == 0 => {
+                let (field_type, reset) = if field.width == 1 {
+                    (
+                        FieldType::Boolean,
+                        field.reset.map(|reset| Value::Bool(reset != 0)),
+                    )
+                } else {
+                    (
+                        FieldType::Unsigned(field.width),
+                        field.reset.map(Value::Unsigned),
+                    )
+                };
+                Ok(Field {
+                    description: description.or(field.description.clone()),
+                    address: Some(Address::Absolute(address)),
+                    access: Some(field.access),
+                    reset,
+                    ..Field::new(&self.name, field_type)
+                })
+            }
+            fields if fields.iter().all(|field| field.width == 1) => {
+                let access = fields[0].access;
+                if fields.iter().any(|field| field.access != access) {
+                    bail!(
+                        "Single-bit fields of register {} at line {} differ in access, which a \
+                         bitfield cannot express",
+                        self.name,
+                        self.line
+                    );
+                }
+                let reset = fields.iter().any(|field| field.reset.is_some()).then(|| {
+                    fields
+                        .iter()
+                        .map(|field| field.reset.unwrap_or(0) << field.lsb)
+                        .sum()
+                });
+                let bits = fields
+                    .iter()
+                    .map(|field| (field.name.to_string(), field.lsb))
+                    .collect();
+                Ok(Field {
+                    description,
+                    address: Some(Address::Absolute(address)),
+                    access: Some(access),
+                    reset: reset.map(Value::Unsigned),
+                    ..Field::new(
+                        &self.name,
+                        FieldType::Bitfield {
+                            length: regwidth,
+                            bits: BitfieldStyle::Discrete(bits),
+                        },
+                    )
+                })
+            }
+            fields
+                if fields
+                    .iter()
+                    .all(|field| field.lsb % 8 == 0 && field.width % 8 == 0) =>
+            {
+                let children = fields
+                    .iter()
+                    .map(|field| Field {
+                        description: field.description.clone(),
+                        address: Some(Address::Absolute(address + field.lsb / 8)),
+                        access: Some(field.access),
+                        reset: field.reset.map(Value::Unsigned),
+                        ..Field::new(field.name, FieldType::Unsigned(field.width))
+                    })
+                    .collect();
+                Ok(Field {
+                    description,
+                    address: Some(Address::Absolute(address)),
+                    contains: Some(OneOrMoreField::More(children)),
+                    ..Field::new(&self.name, FieldType::Set)
+                })
+            }
+            _ => bail!(
+                "Register {} at line {} mixes single-bit fields with multi-bit fields that are \
+                 not byte aligned, which vhdl_doc fields cannot express",
+                self.name,
+                self.line
+            ),
+        }
+    }
+
+    /// Address of this instance within the enclosing block at `base`: offset by its explicit
+    /// address if given, otherwise by `next`, which is None when it cannot be represented
+    fn place(&self, base: u64, next: Option<u64>) -> Result<u64, anyhow::Error> {
+        self.address
+            .or(next)
+            .and_then(|offset| base.checked_add(offset))
+            .ok_or_else(|| self.overflow(base))
+    }
+
+    /// Error for an instance that would extend past the largest representable address
+    fn overflow(&self, address: u64) -> anyhow::Error {
+        anyhow!(
+            "{} {} at line {} placed at 0x{address:x} overflows the address space",
+            self.component.kind,
+            self.name,
+            self.line
+        )
+    }
+
+    /// Converts an addrmap or regfile at `base` into a set, returning it along with the
+    /// address just past its last register. Registers without an explicit address are
+    /// placed at the next multiple of their size, as SystemRDL's compact addressing does.
+    fn block(&self, base: u64) -> Result<(Field, u64), anyhow::Error> {
+        self.check_not_field()?;
+        self.component
+            .check_properties(&self.name, &["name", "desc"])?;
+        let mut next: u64 = 0;
+        let mut children = Vec::new();
+        for child in &self.component.instances {
+            let field = match child.component.kind {
+                Kind::Reg => {
+                    let bytes = child.regwidth()? / 8;
+                    let address = child.place(base, next.checked_next_multiple_of(bytes))?;
+                    let end = address
+                        .checked_add(bytes)
+                        .ok_or_else(|| child.overflow(address))?;
+                    next = end - base;
+                    child.register(address)?
+                }
+                Kind::Regfile | Kind::Addrmap => {
+                    let address = child.place(base, Some(next))?;
+                    let (field, end) = child.block(address)?;
+                    next = end - base;
+                    field
+                }
+                Kind::Field => bail!(
+                    "Field {} at line {} must be placed in a reg",
+                    child.name,
+                    child.line
+                ),
+            };
+            children.push(field);
+        }
+        if children.is_empty() {
+            bail!(
+                "{} {} at line {} has no registers",
+                self.component.kind,
+                self.name,
+                self.line
+            );
+        }
+        let field = Field {
+            description: self.component.description()?,
+            address: Some(Address::Absolute(base)),
+            contains: Some(OneOrMoreField::More(children)),
+            ..Field::new(&self.name, FieldType::Set)
+        };
+        Ok((field, base + next))
+    }
+}
+
+impl MemoryMap {
+    /// Imports the top-level `addrmap` of SystemRDL source. The supported subset covers
+    /// `addrmap`, `regfile`, `reg` and `field` components, whether defined inline or named and
+    /// instantiated later, with explicit `@` addresses, `[msb:lsb]` or `[width]` bit
+    /// selections and `= value` resets. Fields may set `sw`, `hw`, `reset`, `onwrite = woclr`,
+    /// `singlepulse`, `fieldwidth`, `name` and `desc`; registers `regwidth`, `accesswidth`,
+    /// `name` and `desc`. Anything else, including enums, arrays and dynamic assignments, is an
+    /// error rather than being dropped. The protocol is byte addressed with an address range
+    /// rounded up to a power of two.
+    pub fn from_systemrdl(src: &str) -> Result<MemoryMap, anyhow::Error> {
+        let mut parser = Parser {
+            tokens: tokenize(src)?,
+            pos: 0,
+            scopes: vec![HashMap::new()],
+            root: None,
+        };
+        let mut top = Component {
+            kind: Kind::Addrmap,
+            properties: Vec::new(),
+            instances: Vec::new(),
+        };
+        while parser.peek().is_some() {
+            parser.statement(&mut top)?;
+        }
+        if let Some(property) = top.properties.first() {
+            bail!(
+                "Property `{}` at line {} is outside of any component",
+                property.name,
+                property.line
+            );
+        }
+        let root = parser
+            .root
+            .ok_or_else(|| anyhow!("SystemRDL source defines no addrmap"))?;
+        let (field, end) = root.block(0)?;
+        Ok(MemoryMap {
+            protocol: Protocol {
+                name: None,
+                address_max: end
+                    .max(1)
+                    .checked_next_power_of_two()
+                    .map_or(u64::MAX, |size| size - 1),
+                data_min: 1,
+                register_align: None,
+                data_max: None,
+                endianness: Endianness::Little,
+                base_address: None,
+            },
+            field,
+            strict: false,
+        })
+    }
+}
@@ -0,0 +1,117 @@
+use super::{
+    Field, FieldType, MemoryMap, SharedTypes, identifier, render_to_string, sorted_variants,
+};
+use std::io::{self, Write};
+
+impl Field {
+    /// Writes the declarations for this field and any fields it contains. An enum identical to
+    /// one already in `types` is declared as a subtype of it rather than a new type.
+    fn vhdl_declarations<'a, W: Write>(
+        &'a self,
+        prefix: &str,
+        address_width: u32,
+        types: &mut SharedTypes<'a>,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let prefixed = match prefix {
+            "" => identifier(&self.name),
+            _ => format!("{prefix}_{}", identifier(&self.name)),
+        };
+        if matches!(self.field_type, FieldType::Set) {
+            writeln!(out, "\n    -- {}", self.name)?;
+        }
+        if let Some(address) = self.address()
+            && !matches!(self.field_type, FieldType::Reserved(_))
+        {
+            let note = self
+                .access
+                .and_then(|access| access.side_effect())
+                .map(|note| format!(" -- {note}"))
+                .unwrap_or_default();
+            writeln!(
+                out,
+                "    constant {prefixed}_ADDR : unsigned(ADDRESS_WIDTH - 1 downto 0) := {address_width}x\"{address:X}\";{note}"
+            )?;
+        }
+        if let Some((low, length)) = self.packed_bits() {
+            writeln!(
+                out,
+                "    subtype {prefixed}_RANGE is natural range {} downto {low};",
+                low + length - 1
+            )?;
+        }
+        if let FieldType::Enum { length, map } = &self.field_type {
+            if let Some(shared) = types.shared(&self.field_type, &prefixed) {
+                writeln!(out, "    subtype {prefixed}_T is {shared}_T;")?;
+                return self.vhdl_children(&prefixed, address_width, types, out);
+            }
+            let variants = sorted_variants(map);
+            let literals: Vec<String> = variants
+                .iter()
+                .map(|(_, name)| format!("{prefixed}_{}", identifier(name)))
+                .collect();
+            writeln!(out, "    type {prefixed}_T is ({});", literals.join(", "))?;
+            for ((value, _), literal) in variants.iter().zip(&literals).filter(|_| *length > 0) {
+                writeln!(
+                    out,
+                    "    constant {literal}_CODE : unsigned({} downto 0) := {length}d\"{value}\";",
+                    length - 1
+                )?;
+            }
+        }
+        self.vhdl_children(&prefixed, address_width, types, out)
+    }
+
+    /// Writes the declarations of the fields contained in this one
+    fn vhdl_children<'a, W: Write>(
+        &'a self,
+        prefixed: &str,
+        address_width: u32,
+        types: &mut SharedTypes<'a>,
+        out: &mut W,
+    ) -> io::Result<()> {
+        for child in self.children() {
+            child.vhdl_declarations(prefixed, address_width, types, out)?;
+        }
+        Ok(())
+    }
+}
+
+impl MemoryMap {
+    /// Renders a VHDL-2008 package declaring an address constant for every field other than
+    /// reserved space, the bits of the data word holding each field packed by Field.packBits as
+    /// a range subtype, and an enumerated type, with the encoding of each variant, for every
+    /// enum field. Constants of set members are prefixed with the names of their enclosing sets.
+    /// Must be called after [`MemoryMap::elaborate`] so that addresses are populated.
+    pub fn to_vhdl_package(&self, package_name: &str) -> String {
+        render_to_string(|out| self.write_vhdl_package(package_name, out))
+    }
+
+    /// Streams the package rendered by [`MemoryMap::to_vhdl_package`] to `out`
+    pub fn write_vhdl_package<W: Write>(&self, package_name: &str, out: &mut W) -> io::Result<()> {
+        let address_width = (u64::BITS - self.protocol.address_max.leading_zeros()).max(1);
+        writeln!(
+            out,
+            "-- {} register definitions generated by vhdl_doc",
+            self.field.name
+        )?;
+        out.write_all(b"library ieee;\n")?;
+        out.write_all(b"use ieee.std_logic_1164.all;\n")?;
+        out.write_all(b"use ieee.numeric_std.all;\n\n")?;
+        writeln!(out, "package {package_name} is\n")?;
+        writeln!(
+            out,
+            "    constant ADDRESS_WIDTH : natural := {address_width};"
+        )?;
+        let mut types = SharedTypes::default();
+        if matches!(self.field.field_type, FieldType::Set) {
+            for child in self.field.children() {
+                child.vhdl_declarations("", address_width, &mut types, out)?;
+            }
+        } else {
+            self.field
+                .vhdl_declarations("", address_width, &mut types, out)?;
+        }
+        writeln!(out, "\nend package {package_name};")
+    }
+}
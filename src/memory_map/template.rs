@@ -0,0 +1,22 @@
+use crate::output::write_if_changed;
+use std::io;
+use std::path::Path;
+
+/// Commented starter memory map covering every field type
+pub const MEMORY_MAP_TEMPLATE: &str = include_str!("template.toml");
+
+/// Writes the memory map template to `path`, refusing to replace an existing file unless
+/// `force` is set.
+pub fn init_memory_map(path: &Path, force: bool) -> io::Result<()> {
+    if path.exists() && !force {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "{} already exists; use --force to overwrite it",
+                path.display()
+            ),
+        ));
+    }
+    write_if_changed(path, MEMORY_MAP_TEMPLATE.as_bytes())?;
+    Ok(())
+}
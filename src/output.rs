@@ -0,0 +1,72 @@
+use log::info;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Writes `content` to `path` only if the file does not already hold identical content,
+/// keeping timestamps stable for downstream builds. Returns whether the file was written.
+pub fn write_if_changed<P: AsRef<Path>>(path: P, content: &[u8]) -> io::Result<bool> {
+    let path = path.as_ref();
+    if fs::read(path).is_ok_and(|existing| existing == content) {
+        return Ok(false);
+    }
+    fs::write(path, content)?;
+    info!("Updated {}", path.display());
+    Ok(true)
+}
+
+/// Streams the output of `render` to `path` without holding the whole document in memory.
+/// The output is written to a sibling temporary file which only replaces `path` when its
+/// content differs, as with [`write_if_changed`]. Returns whether the file was written.
+pub fn stream_if_changed<P, E>(
+    path: P,
+    render: impl FnOnce(&mut BufWriter<File>) -> Result<(), E>,
+) -> Result<bool, E>
+where
+    P: AsRef<Path>,
+    E: From<io::Error>,
+{
+    let path = path.as_ref();
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    let mut out = BufWriter::new(File::create(&temp_path)?);
+    let rendered = render(&mut out).and_then(|()| Ok(out.flush()?));
+    drop(out);
+    if let Err(err) = rendered {
+        let _ = fs::remove_file(&temp_path);
+        return Err(err);
+    }
+    if same_content(path, &temp_path)? {
+        fs::remove_file(&temp_path)?;
+        return Ok(false);
+    }
+    fs::rename(&temp_path, path)?;
+    info!("Updated {}", path.display());
+    Ok(true)
+}
+
+/// Compares two files chunk by chunk. A missing `existing` file never matches.
+fn same_content(existing: &Path, candidate: &Path) -> io::Result<bool> {
+    let Ok(existing) = File::open(existing) else {
+        return Ok(false);
+    };
+    let candidate = File::open(candidate)?;
+    if existing.metadata()?.len() != candidate.metadata()?.len() {
+        return Ok(false);
+    }
+    let mut existing = BufReader::new(existing);
+    let mut candidate = BufReader::new(candidate);
+    let mut left = [0; 8192];
+    let mut right = [0; 8192];
+    loop {
+        let read = existing.read(&mut left)?;
+        if read == 0 {
+            return Ok(true);
+        }
+        candidate.read_exact(&mut right[..read])?;
+        if left[..read] != right[..read] {
+            return Ok(false);
+        }
+    }
+}
@@ -1,15 +1,93 @@
-use image::{Rgb, RgbImage};
+use crate::output::write_if_changed;
+use anyhow::bail;
+use image::{ImageFormat, Rgb, RgbImage};
+use std::io::Cursor;
+use std::ops::Range;
 use std::path::PathBuf;
 
-pub fn make_symbol(doc_path: PathBuf) {
-    let mut img = RgbImage::new(32, 32);
-    for x in 15..=17 {
-        for y in 8..24 {
-            img.put_pixel(x, y, Rgb([255, 0, 0]));
-            img.put_pixel(y, x, Rgb([255, 0, 0]));
+/// Smallest symbol edge length, in pixels, that still draws a recognizable cross
+pub const MIN_SYMBOL_SIZE: u32 = 8;
+
+/// Edge length of the SVG symbol's coordinate system
+const SVG_VIEW_SIZE: u32 = 32;
+
+/// Symbol color used when none is configured
+pub const DEFAULT_SYMBOL_COLOR: [u8; 3] = [255, 0, 0];
+
+/// Parses a `#RRGGBB` hex color string
+pub fn parse_color(color: &str) -> Result<[u8; 3], anyhow::Error> {
+    let digits = color
+        .strip_prefix('#')
+        .filter(|digits| digits.len() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit()));
+    let Some(digits) = digits else {
+        bail!("Invalid color {color}; expected a #RRGGBB hex string");
+    };
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16);
+    Ok([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Geometry of the cross glyph on a square grid
+struct Cross {
+    /// Extent of each bar across its thickness
+    bar: Range<u32>,
+    /// Extent of each bar along its length
+    arm: Range<u32>,
+}
+
+impl Cross {
+    /// The bars span the middle half of the grid and are 3/32 of its size thick
+    fn new(size: u32) -> Self {
+        let thickness = (3 * size).div_ceil(32).max(1);
+        let bar_start = size / 2 - thickness / 2;
+        Cross {
+            bar: bar_start..bar_start + thickness,
+            arm: size / 4..size - size / 4,
         }
     }
-    let mut out_path = doc_path.into_os_string();
-    out_path.push("/test.png");
-    img.save(out_path).unwrap();
+
+    /// The vertical and horizontal bars as (x, y, width, height) rectangles
+    fn rects(&self) -> [(u32, u32, u32, u32); 2] {
+        let thickness = self.bar.end - self.bar.start;
+        let length = self.arm.end - self.arm.start;
+        [
+            (self.bar.start, self.arm.start, thickness, length),
+            (self.arm.start, self.bar.start, length, thickness),
+        ]
+    }
+}
+
+/// Writes a `size` x `size` PNG symbol drawn in `color` to `doc_path`
+pub fn make_symbol(doc_path: PathBuf, size: u32, color: [u8; 3]) -> Result<(), anyhow::Error> {
+    if size < MIN_SYMBOL_SIZE {
+        bail!("Symbol size {size} is smaller than the minimum of {MIN_SYMBOL_SIZE} pixels");
+    }
+    let mut img = RgbImage::new(size, size);
+    for (x, y, width, height) in Cross::new(size).rects() {
+        for px in x..x + width {
+            for py in y..y + height {
+                img.put_pixel(px, py, Rgb(color));
+            }
+        }
+    }
+    let mut png = Vec::new();
+    img.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    write_if_changed(doc_path.join("test.png"), &png)?;
+    Ok(())
+}
+
+/// Writes a scalable SVG version of the symbol drawn in `color` to `doc_path`
+pub fn make_symbol_svg(doc_path: PathBuf, color: [u8; 3]) -> Result<(), anyhow::Error> {
+    let [red, green, blue] = color;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {SVG_VIEW_SIZE} {SVG_VIEW_SIZE}\">\n"
+    );
+    for (x, y, width, height) in Cross::new(SVG_VIEW_SIZE).rects() {
+        svg.push_str(&format!(
+            "  <rect x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{height}\" \
+             fill=\"#{red:02x}{green:02x}{blue:02x}\"/>\n"
+        ));
+    }
+    svg.push_str("</svg>\n");
+    write_if_changed(doc_path.join("test.svg"), svg.as_bytes())?;
+    Ok(())
 }
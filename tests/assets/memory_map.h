@@ -0,0 +1,39 @@
+/* Example Memory Map register definitions generated by vhdl_doc */
+#ifndef MEMORY_MAP_H
+#define MEMORY_MAP_H
+
+#include <stdint.h>
+
+/* A nice group */
+#define A_NICE_GROUP_ADDR 0x00000000u
+#define A_NICE_GROUP_DESCRIPTION_STRING_ADDR 0x00000000u
+#define A_NICE_GROUP_A_BITFIELD_ADDR 0x00000014u
+#define A_NICE_GROUP_A_BITFIELD_ZERO_SHIFT 0u
+#define A_NICE_GROUP_A_BITFIELD_ZERO_MASK 0x0001u
+#define A_NICE_GROUP_A_BITFIELD_ONE_SHIFT 1u
+#define A_NICE_GROUP_A_BITFIELD_ONE_MASK 0x0002u
+#define A_NICE_GROUP_A_BITFIELD_TWO_SHIFT 2u
+#define A_NICE_GROUP_A_BITFIELD_TWO_MASK 0x0004u
+#define A_NICE_GROUP_A_BITFIELD_TEN_SHIFT 10u
+#define A_NICE_GROUP_A_BITFIELD_TEN_MASK 0x0400u
+#define A_NICE_GROUP_ANOTHER_BITFIELD_ADDR 0x00000016u
+#define A_NICE_GROUP_ANOTHER_BITFIELD_ITEM0_SHIFT 0u
+#define A_NICE_GROUP_ANOTHER_BITFIELD_ITEM0_MASK 0x01u
+#define A_NICE_GROUP_ANOTHER_BITFIELD_ITEM1_SHIFT 1u
+#define A_NICE_GROUP_ANOTHER_BITFIELD_ITEM1_MASK 0x02u
+#define A_NICE_GROUP_ANOTHER_BITFIELD_ITEM2_SHIFT 2u
+#define A_NICE_GROUP_ANOTHER_BITFIELD_ITEM2_MASK 0x04u
+#define A_NICE_GROUP_ANOTHER_BITFIELD_ITEM3_SHIFT 3u
+#define A_NICE_GROUP_ANOTHER_BITFIELD_ITEM3_MASK 0x08u
+
+typedef struct {
+    uint8_t description_string[20];
+    uint16_t a_bitfield;
+    uint8_t another_bitfield;
+} example_memory_map_a_nice_group_t;
+
+typedef struct {
+    example_memory_map_a_nice_group_t a_nice_group;
+} example_memory_map_t;
+
+#endif /* MEMORY_MAP_H */
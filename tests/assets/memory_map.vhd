@@ -0,0 +1,16 @@
+-- Example Memory Map register definitions generated by vhdl_doc
+library ieee;
+use ieee.std_logic_1164.all;
+use ieee.numeric_std.all;
+
+package memory_map_pkg is
+
+    constant ADDRESS_WIDTH : natural := 32;
+
+    -- A nice group
+    constant A_NICE_GROUP_ADDR : unsigned(ADDRESS_WIDTH - 1 downto 0) := 32x"0";
+    constant A_NICE_GROUP_DESCRIPTION_STRING_ADDR : unsigned(ADDRESS_WIDTH - 1 downto 0) := 32x"0";
+    constant A_NICE_GROUP_A_BITFIELD_ADDR : unsigned(ADDRESS_WIDTH - 1 downto 0) := 32x"14";
+    constant A_NICE_GROUP_ANOTHER_BITFIELD_ADDR : unsigned(ADDRESS_WIDTH - 1 downto 0) := 32x"16";
+
+end package memory_map_pkg;
@@ -0,0 +1,422 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Fresh output directory for a single test
+fn doc_path(test: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("vhdl_doc_cli_{test}"));
+    let _ = fs::remove_dir_all(&path);
+    path
+}
+
+fn vhdl_doc() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_vhdl_doc"))
+}
+
+/// Checks a TOML memory map read from standard input, returning what was logged
+fn check_stdin_toml(toml: &str) -> String {
+    let mut child = vhdl_doc()
+        .args(["--check", "--input-format", "toml", "--source-path", "-"])
+        .env_remove("RUST_LOG")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run vhdl_doc");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(toml.as_bytes())
+        .unwrap();
+    let output = child
+        .wait_with_output()
+        .expect("Failed to wait on vhdl_doc");
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    assert!(output.status.success(), "{stderr}");
+    stderr
+}
+
+#[test]
+pub fn format_markdown() {
+    let doc_path = doc_path("format_markdown");
+    let output = vhdl_doc()
+        .args([
+            "--source-path",
+            "tests/assets/memory_map.toml",
+            "--format",
+            "markdown",
+        ])
+        .arg("--doc-path")
+        .arg(&doc_path)
+        .output()
+        .expect("Failed to run vhdl_doc");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let markdown =
+        fs::read_to_string(doc_path.join("memory_map.md")).expect("Failed to read output");
+    assert!(markdown.starts_with("# Example Memory Map\n"));
+    assert!(markdown.contains("| 0x00000014 | A nice group.A bitfield | bitfield(16) | rw |"));
+}
+
+#[test]
+pub fn format_unknown() {
+    let output = vhdl_doc()
+        .args(["--format", "pdf"])
+        .output()
+        .expect("Failed to run vhdl_doc");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("invalid value 'pdf'"));
+    assert!(stderr.contains("c-header"));
+}
+
+#[test]
+pub fn emit_schema() {
+    let doc_path = doc_path("emit_schema");
+    fs::create_dir_all(&doc_path).unwrap();
+    let schema_path = doc_path.join("memory_map.schema.json");
+    let output = vhdl_doc()
+        .arg("--emit-schema")
+        .arg(&schema_path)
+        .output()
+        .expect("Failed to run vhdl_doc");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output.stdout.is_empty());
+    let schema = fs::read_to_string(&schema_path).expect("Failed to read schema");
+    let schema: serde_json::Value = serde_json::from_str(&schema).expect("Failed to parse schema");
+    assert_eq!(schema["title"], "VHDL Memory Map");
+}
+
+/// Renders the sample map as JSON with the given layout arguments
+fn json_output(test: &str, layout: &[&str]) -> String {
+    let doc_path = doc_path(test);
+    let output = vhdl_doc()
+        .args(["--source-path", "tests/assets/memory_map.toml"])
+        .args(layout)
+        .arg("--doc-path")
+        .arg(&doc_path)
+        .output()
+        .expect("Failed to run vhdl_doc");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::read_to_string(doc_path.join("memory_map.json")).expect("Failed to read JSON")
+}
+
+#[test]
+pub fn json_indent_and_compact() {
+    let compact = json_output("json_compact", &["--compact"]);
+    let indented = json_output("json_indent", &["--indent", "2"]);
+    assert!(compact.starts_with(r#"{"protocol":{"name":"#), "{compact}");
+    assert!(!compact.contains('\n'));
+    assert!(
+        indented.starts_with("{\n  \"protocol\": {\n    \"name\": "),
+        "{indented}"
+    );
+    let compact: serde_json::Value = serde_json::from_str(&compact).expect("Failed to parse JSON");
+    let indented: serde_json::Value =
+        serde_json::from_str(&indented).expect("Failed to parse JSON");
+    assert_eq!(compact, indented);
+}
+
+#[test]
+pub fn check_valid() {
+    let doc_path = doc_path("check_valid");
+    let output = vhdl_doc()
+        .args(["--check", "--source-path", "tests/assets/memory_map.toml"])
+        .arg("--doc-path")
+        .arg(&doc_path)
+        .output()
+        .expect("Failed to run vhdl_doc");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("OK"));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("elaborated 5 fields"));
+    assert!(!doc_path.exists());
+}
+
+#[test]
+pub fn check_overlapping() {
+    let doc_path = doc_path("check_overlapping");
+    let output = vhdl_doc()
+        .args([
+            "--check",
+            "--source-path",
+            "tests/assets/overlapping_map.toml",
+        ])
+        .arg("--doc-path")
+        .arg(&doc_path)
+        .output()
+        .expect("Failed to run vhdl_doc");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("overlaps the preceding field"));
+    assert!(!doc_path.exists());
+}
+
+#[test]
+pub fn stdin_json() {
+    let doc_path = doc_path("stdin_json");
+    let mut child = vhdl_doc()
+        .args(["--source-path", "-", "--format", "markdown"])
+        .arg("--doc-path")
+        .arg(&doc_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run vhdl_doc");
+    let json = fs::read("tests/assets/memory_map.json").expect("Failed to read file");
+    child.stdin.take().unwrap().write_all(&json).unwrap();
+    let output = child
+        .wait_with_output()
+        .expect("Failed to wait on vhdl_doc");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let markdown =
+        fs::read_to_string(doc_path.join("memory_map.md")).expect("Failed to read output");
+    assert!(markdown.contains("| Address | Name |"));
+}
+
+#[test]
+pub fn merge_directory() {
+    let doc_path = doc_path("merge_directory");
+    let output = vhdl_doc()
+        .args([
+            "--source-path",
+            "tests/assets/merge",
+            "--format",
+            "markdown",
+        ])
+        .arg("--doc-path")
+        .arg(&doc_path)
+        .output()
+        .expect("Failed to run vhdl_doc");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let markdown = fs::read_to_string(doc_path.join("merge.md")).expect("Failed to read output");
+    assert!(markdown.starts_with("# merge\n"));
+    assert!(markdown.contains("| 0x04 | Control.mode |"));
+    assert!(markdown.contains("| 0x08 | Status | set |"));
+    assert!(markdown.contains("| 0x0C | Status.count |"));
+}
+
+#[test]
+pub fn merge_conflicting_protocols() {
+    let output = vhdl_doc()
+        .args([
+            "--check",
+            "--source-path",
+            "tests/assets/merge/control.toml",
+            "tests/assets/overlapping_map.toml",
+        ])
+        .output()
+        .expect("Failed to run vhdl_doc");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(
+        "Memory map Overlapping declares a protocol that conflicts with the protocol of Control"
+    ));
+}
+
+#[test]
+pub fn verbose_logs_addresses() {
+    let output = vhdl_doc()
+        .args([
+            "-vv",
+            "--check",
+            "--source-path",
+            "tests/assets/memory_map.toml",
+        ])
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("Failed to run vhdl_doc");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("DEBUG"));
+    assert!(
+        stderr.contains(
+            "Field A nice group.A bitfield assigned address 0x14 with a width of 2 bytes"
+        )
+    );
+}
+
+#[test]
+pub fn quiet_by_default() {
+    let output = vhdl_doc()
+        .args(["--check", "--source-path", "tests/assets/memory_map.toml"])
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("Failed to run vhdl_doc");
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+pub fn init_checks_quietly() {
+    let doc_path = doc_path("init_checks_quietly");
+    fs::create_dir_all(&doc_path).expect("Failed to create directory");
+    let template = doc_path.join("memory_map.toml");
+    let output = vhdl_doc()
+        .arg("--init")
+        .arg(&template)
+        .output()
+        .expect("Failed to run vhdl_doc");
+    assert!(output.status.success());
+    let output = vhdl_doc()
+        .arg("--check")
+        .arg("--source-path")
+        .arg(&template)
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("Failed to run vhdl_doc");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.is_empty(), "{stderr}");
+}
+
+#[test]
+pub fn logs_updated_output_when_verbose() {
+    let doc_path = doc_path("logs_updated_output_when_verbose");
+    let run = |verbose: &[&str]| {
+        let output = vhdl_doc()
+            .args(["--source-path", "tests/assets/memory_map.toml"])
+            .args(["--format", "markdown"])
+            .args(verbose)
+            .arg("--doc-path")
+            .arg(&doc_path)
+            .env_remove("RUST_LOG")
+            .output()
+            .expect("Failed to run vhdl_doc");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    };
+    let stderr = run(&[]);
+    assert!(!stderr.contains("memory_map.md"), "{stderr}");
+    fs::remove_file(doc_path.join("memory_map.md")).expect("Failed to remove output");
+    let stderr = run(&["-v"]);
+    assert!(stderr.contains("Updated "), "{stderr}");
+    assert!(stderr.contains("memory_map.md"), "{stderr}");
+}
+
+#[test]
+pub fn stdin_yaml() {
+    let mut child = vhdl_doc()
+        .args(["--check", "--input-format", "yaml", "--source-path", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run vhdl_doc");
+    let yaml = fs::read("tests/assets/memory_map.yaml").expect("Failed to read file");
+    child.stdin.take().unwrap().write_all(&yaml).unwrap();
+    let output = child
+        .wait_with_output()
+        .expect("Failed to wait on vhdl_doc");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("elaborated 5 fields"));
+}
+
+#[test]
+pub fn warns_read_only_without_value() {
+    let stderr = check_stdin_toml(
+        r#"name = "Block"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "status"
+type = "set"
+access = "r"
+contains = [
+    { name = "count", type.unsigned = 8 },
+    { name = "flags", type.bitfield = { length = 2, bits = ["a", "b"] } },
+    { name = "version", type.unsigned = 8, value = 3 },
+]
+
+[[contains]]
+name = "control"
+access = "rw"
+type.unsigned = 8
+"#,
+    );
+    assert!(stderr.contains("WARN"), "{stderr}");
+    assert!(stderr.contains("Read-only field status.count documents no value"));
+    assert!(stderr.contains("Read-only field status.flags documents no value"));
+    assert!(!stderr.contains("status.version"));
+    assert!(!stderr.contains("control"));
+}
+
+#[test]
+pub fn warns_write_only_with_value() {
+    let stderr = check_stdin_toml(
+        r#"name = "Block"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "command"
+access = "w"
+type.unsigned = 8
+value = 1
+
+[[contains]]
+name = "trigger"
+access = "w"
+type.unsigned = 8
+
+[[contains]]
+name = "control"
+access = "rw"
+type.unsigned = 8
+value = 1
+
+[[contains]]
+name = "version"
+access = "r"
+type.unsigned = 8
+value = 1
+"#,
+    );
+    assert!(
+        stderr.contains("Write-only field command declares a value that can never be read back"),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("trigger"));
+    assert!(!stderr.contains("control"));
+    assert!(!stderr.contains("version"));
+}
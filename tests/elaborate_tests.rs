@@ -0,0 +1,1242 @@
+use serde_json::Value;
+use vhdl_doc::memory_map::schema::{Access, Bound, ElaborationError, MemoryMap};
+
+fn elaborate(contents: &str) -> Value {
+    let mut memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    memory_map.elaborate().expect("Failed to elaborate");
+    serde_json::to_value(&memory_map).expect("Failed to serialize to JSON value")
+}
+
+fn elaborate_err(contents: &str) -> String {
+    let mut memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    memory_map
+        .elaborate()
+        .expect_err("Elaborated an invalid map")
+        .to_string()
+}
+
+#[test]
+pub fn numeric_ranges() {
+    let json = elaborate(
+        r#"name = "Numbers"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "u16"
+type.unsigned = 16
+
+[[contains]]
+name = "s8"
+type.signed = 8
+
+[[contains]]
+name = "ufixed"
+type.ufixed = { high = 11, low = -4 }
+
+[[contains]]
+name = "sfixed"
+type.sfixed = { high = 3, low = -4 }
+
+[[contains]]
+name = "bounded"
+type.unsigned = 16
+min = 10
+max = 1000.5
+"#,
+    );
+    let ranges: Vec<&str> = json["contains"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| field["range"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        ranges,
+        [
+            "0 .. 65535",
+            "-128 .. 127",
+            "0.0 .. 4095.9375",
+            "-8.0 .. 7.9375",
+            "10 .. 1000.5"
+        ]
+    );
+}
+
+#[test]
+pub fn fixed_width_overflow() {
+    for field_type in [
+        "ufixed = { high = 9223372036854775807, low = -1 }",
+        "sfixed = { high = 9223372036854775807, low = -9223372036854775808 }",
+        "ufixed = { high = 0, low = -4294967296 }",
+    ] {
+        let err = elaborate_err(&format!(
+            r#"name = "Wide"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "wide"
+type.{field_type}
+"#
+        ));
+        assert!(
+            err.contains("beyond the widths that can be represented"),
+            "{err}"
+        );
+    }
+}
+
+#[test]
+pub fn set_range_spans_children() {
+    let json = elaborate(
+        r#"name = "Outer"
+type = "set"
+
+[protocol]
+addressMax = 0xFFFF
+dataMin = 1
+
+[[contains]]
+name = "first"
+type.unsigned = 32
+
+[[contains]]
+name = "Inner"
+type = "set"
+address = 0x10
+
+[[contains.contains]]
+name = "second"
+type.unsigned = 16
+
+[[contains.contains]]
+name = "third"
+type.string = 4
+"#,
+    );
+    assert_eq!(json["range"], "0x0 .. 0x15");
+    assert_eq!(json["contains"][1]["range"], "0x10 .. 0x15");
+}
+
+#[test]
+pub fn empty_set_range() {
+    let json = elaborate(
+        r#"name = "Empty"
+type = "set"
+contains = []
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#,
+    );
+    assert!(json.get("range").is_none());
+}
+
+#[test]
+pub fn descriptive_ranges() {
+    let json = elaborate(
+        r#"name = "Descriptive"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "version"
+type.string = 8
+
+[[contains]]
+name = "mode"
+type.enum.length = 2
+type.enum.map = { run = 1, idle = 0, halt = 2 }
+
+[[contains]]
+name = "status"
+type.bitfield.length = 8
+type.bitfield.bits = ["ready", "error", "busy"]
+
+[[contains]]
+name = "interrupts"
+type.bitfield.length = 16
+type.bitfield.bits = { rx = 0, tx = 1, overflow = 15, parity = 4 }
+"#,
+    );
+    let ranges: Vec<&str> = json["contains"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| field["range"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        ranges,
+        [
+            "8 ASCII bytes",
+            "0=idle, 1=run, 2=halt",
+            "b0=ready, b1=error, b2=busy, b3..b7=Reserved",
+            "b0=rx, b1=tx, b2..b3=Reserved, b4=parity, b5..b14=Reserved, b15=overflow"
+        ]
+    );
+}
+
+#[test]
+pub fn address_wraparound() {
+    let err = elaborate_err(
+        r#"name = "Wrap"
+type = "set"
+
+[protocol]
+addressMax = "0xFFFF_FFFF_FFFF_FFFF"
+dataMin = 1
+
+[[contains]]
+name = "pinned"
+address = "0xFFFF_FFFF_FFFF_FFFF"
+type.unsigned = 16
+
+[[contains]]
+name = "wrapped"
+type.unsigned = 8
+"#,
+    );
+    assert!(err.contains("pinned"));
+    assert!(err.contains("overflows the address space"));
+}
+
+#[test]
+pub fn aligned_addresses() {
+    let json = elaborate(
+        r#"name = "Aligned"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 4
+
+[[contains]]
+name = "byte"
+type.unsigned = 8
+
+[[contains]]
+name = "packed"
+type.unsigned = 8
+
+[[contains]]
+name = "explicit"
+address = 0x10
+type.unsigned = 8
+"#,
+    );
+    assert_eq!(json["contains"][0]["address"], 0x0);
+    assert_eq!(json["contains"][1]["address"], 0x4);
+    assert_eq!(json["contains"][2]["address"], 0x10);
+}
+
+#[test]
+pub fn misaligned_address() {
+    let err = elaborate_err(
+        r#"name = "Misaligned"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 4
+
+[[contains]]
+name = "explicit"
+address = 0x12
+type.unsigned = 8
+"#,
+    );
+    assert!(err.contains("address 0x12 is not aligned to the data_min of 4 bytes"));
+}
+
+#[test]
+pub fn array_default_stride() {
+    let json = elaborate(
+        r#"name = "Arrays"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "status"
+type.array = { count = 16, element.unsigned = 32 }
+
+[[contains]]
+name = "after"
+type.unsigned = 8
+"#,
+    );
+    assert_eq!(json["contains"][0]["range"], "0x0 .. 0x3c (16 x 4 bytes)");
+    assert_eq!(json["contains"][1]["address"], 0x40);
+}
+
+#[test]
+pub fn array_explicit_stride() {
+    let json = elaborate(
+        r#"name = "Arrays"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "status"
+type.array = { count = 16, stride = 8, element.unsigned = 32 }
+
+[[contains]]
+name = "after"
+type.unsigned = 8
+"#,
+    );
+    assert_eq!(json["contains"][0]["range"], "0x0 .. 0x78 (16 x 8 bytes)");
+    assert_eq!(json["contains"][1]["address"], 0x80);
+}
+
+#[test]
+pub fn array_element_checked_like_field() {
+    let array = |element: &str| {
+        format!(
+            r#"name = "Arrays"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "status"
+type.array = {{ count = 4, element.{element} }}
+"#
+        )
+    };
+    let err = elaborate_err(&array("unsigned = 0"));
+    assert!(
+        err.contains("Field status has an unsigned type with zero length"),
+        "{err}"
+    );
+    let err = elaborate_err(&array("array = { count = 2, element.signed = 0 }"));
+    assert!(
+        err.contains("Field status has a signed type with zero length"),
+        "{err}"
+    );
+    let err = elaborate_err(&array("enum = { length = 1, map = { a = 0, b = 2 } }"));
+    assert!(err.contains("Field status is an enum of 1 bits"), "{err}");
+    let err = elaborate_err(&format!("{}value = 300\n", array("unsigned = 8")));
+    assert!(
+        err.contains("Field status value 300 does not fit in its unsigned(8) type"),
+        "{err}"
+    );
+}
+
+#[test]
+pub fn nested_array_overflow() {
+    let err = elaborate_err(
+        r#"name = "Arrays"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "huge"
+type.array = { count = 2, element.array = { count = 4294967295, stride = 281474976710655, element.unsigned = 8 } }
+"#,
+    );
+    assert!(
+        err.contains("Field huge at 0x0 overflows the address space"),
+        "{err}"
+    );
+}
+
+fn bounded(value: &str, min: &str, max: &str) -> String {
+    format!(
+        r#"name = "Bounded"
+type.unsigned = 16
+value = {value}
+min = {min}
+max = {max}
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#
+    )
+}
+
+#[test]
+pub fn value_within_bounds() {
+    let json = elaborate(&bounded("50", "10", "100"));
+    assert_eq!(json["range"], "10 .. 100");
+}
+
+#[test]
+pub fn value_below_min() {
+    let err = elaborate_err(&bounded("5", "10", "100"));
+    assert!(err.contains("Field Bounded value 5 is below its min of 10"));
+}
+
+#[test]
+pub fn value_above_max() {
+    let err = elaborate_err(&bounded("101", "10", "100"));
+    assert!(err.contains("Field Bounded value 101 is above its max of 100"));
+}
+
+#[test]
+pub fn min_above_max() {
+    let err = elaborate_err(&bounded("50", "100", "10"));
+    assert!(err.contains("Field Bounded min of 100 is greater than its max of 10"));
+}
+
+fn typed_value(field_type: &str, value: &str) -> String {
+    format!(
+        r#"name = "Typed"
+type.{field_type}
+{value}
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#
+    )
+}
+
+#[test]
+pub fn unsigned_value_outside_type() {
+    let err = elaborate_err(&typed_value("unsigned = 8", "value = 300"));
+    assert!(
+        err.contains("Field Typed value 300 does not fit in its unsigned(8) type"),
+        "{err}"
+    );
+    let err = elaborate_err(&typed_value("unsigned = 8", "reset = -5"));
+    assert!(err.contains("Field Typed reset -5 does not fit"), "{err}");
+    elaborate(&typed_value("unsigned = 8", "value = 255\nreset = 0"));
+}
+
+#[test]
+pub fn signed_value_outside_type() {
+    let err = elaborate_err(&typed_value("signed = 8", "value = 128"));
+    assert!(
+        err.contains("Field Typed value 128 does not fit in its signed(8) type"),
+        "{err}"
+    );
+    let err = elaborate_err(&typed_value("signed = 8", "reset = -129"));
+    assert!(err.contains("Field Typed reset -129 does not fit"), "{err}");
+    elaborate(&typed_value("signed = 8", "value = 127\nreset = -128"));
+}
+
+#[test]
+pub fn ufixed_value_outside_type() {
+    let field_type = "ufixed = { high = 3, low = -4 }";
+    let err = elaborate_err(&typed_value(field_type, "value = 16.0"));
+    assert!(
+        err.contains("Field Typed value 16 does not fit in its ufixed(3 downto -4) type"),
+        "{err}"
+    );
+    let err = elaborate_err(&typed_value(field_type, "reset = -0.5"));
+    assert!(err.contains("Field Typed reset -0.5 does not fit"), "{err}");
+    elaborate(&typed_value(field_type, "value = 15.9375"));
+}
+
+#[test]
+pub fn sfixed_value_outside_type() {
+    let field_type = "sfixed = { high = 3, low = -4 }";
+    let err = elaborate_err(&typed_value(field_type, "value = 8.0"));
+    assert!(
+        err.contains("Field Typed value 8 does not fit in its sfixed(3 downto -4) type"),
+        "{err}"
+    );
+    let err = elaborate_err(&typed_value(field_type, "reset = -8.5"));
+    assert!(err.contains("Field Typed reset -8.5 does not fit"), "{err}");
+    elaborate(&typed_value(field_type, "value = 7.9375\nreset = -8.0"));
+}
+
+#[test]
+pub fn string_value_longer_than_type() {
+    let err = elaborate_err(&typed_value("string = 4", "value = \"too long\""));
+    assert!(
+        err.contains("Field Typed value too long does not fit in its string(4) type"),
+        "{err}"
+    );
+    let err = elaborate_err(&typed_value("string = 4", "reset = \"12345\""));
+    assert!(
+        err.contains("Field Typed reset 12345 does not fit"),
+        "{err}"
+    );
+    elaborate(&typed_value("string = 4", "value = \"abcd\""));
+}
+
+fn enum_with_value(value: &str) -> String {
+    format!(
+        r#"name = "Mode"
+type.enum.length = 2
+type.enum.map = {{ idle = 0, run = 1, halt = 2 }}
+value = {value}
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#
+    )
+}
+
+#[test]
+pub fn enum_value_matches_variant() {
+    elaborate(&enum_with_value(r#""run""#));
+    elaborate(&enum_with_value("2"));
+}
+
+#[test]
+pub fn enum_value_misspelled() {
+    let err = elaborate_err(&enum_with_value(r#""rnu""#));
+    assert!(err.contains(r#"Field Mode value "rnu" is not a variant of its enum"#));
+    assert!(err.contains("valid variants are: halt, idle, run"));
+}
+
+#[test]
+pub fn enum_value_unmapped() {
+    let err = elaborate_err(&enum_with_value("3"));
+    assert!(err.contains("Field Mode value 3 is not a variant of its enum"));
+}
+
+#[test]
+pub fn enum_length_too_short() {
+    let contents = r#"name = "Mode"
+type.enum.length = 2
+type.enum.map = { idle = 0, run = 1, halt = 2, sleep = 3, fault = 4 }
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#;
+    let err = elaborate_err(contents);
+    assert!(err.contains(
+        "Field Mode is an enum of 2 bits, too short for its largest index 4; the length must be at least 3"
+    ));
+    elaborate(&contents.replace("length = 2", "length = 3"));
+}
+
+#[test]
+pub fn duplicate_names_in_set() {
+    let err = elaborate_err(
+        r#"name = "Registers"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "DATA"
+type.unsigned = 8
+
+[[contains]]
+name = "data"
+type.unsigned = 8
+"#,
+    );
+    assert!(err.contains("Set Registers contains more than one field named data"));
+}
+
+#[test]
+pub fn duplicate_identifiers_in_set() {
+    let err = elaborate_err(
+        r#"name = "Registers"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "Control Reg"
+type.unsigned = 8
+
+[[contains]]
+name = "control_reg"
+type.unsigned = 8
+"#,
+    );
+    assert!(err.contains("Set Registers contains more than one field named control_reg"));
+}
+
+#[test]
+pub fn boolean_occupies_data_min() {
+    let json = elaborate(
+        r#"name = "Flags"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 2
+
+[[contains]]
+name = "enable"
+type = "boolean"
+value = false
+
+[[contains]]
+name = "after"
+type.unsigned = 8
+"#,
+    );
+    assert_eq!(json["contains"][0]["range"], "false | true");
+    assert_eq!(json["contains"][1]["address"], 2);
+}
+
+#[test]
+pub fn explicit_address_overlap() {
+    let err = elaborate_err(
+        r#"name = "Overlapping"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "wide"
+type.unsigned = 32
+
+[[contains]]
+name = "narrow"
+address = 0x02
+type.unsigned = 8
+"#,
+    );
+    assert!(
+        err.contains("Field narrow address 0x2 overlaps the preceding field, which ends at 0x3")
+    );
+}
+
+#[test]
+pub fn reset_checked_like_value() {
+    let err = elaborate_err(
+        r#"name = "Level"
+type.unsigned = 8
+value = 10
+reset = 200
+max = 100
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#,
+    );
+    assert!(err.contains("Field Level reset 200 is above its max of 100"));
+    let err = elaborate_err(
+        r#"name = "Mode"
+type.enum = { length = 2, map = { idle = 0, run = 1 } }
+value = "idle"
+reset = "halt"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#,
+    );
+    assert!(err.contains("Field Mode reset \"halt\" is not a variant of its enum"));
+    let err = elaborate_err(
+        r#"name = "Enable"
+type = "boolean"
+reset = 1
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#,
+    );
+    assert!(err.contains("Field Enable is a boolean and its reset must be true or false"));
+}
+
+#[test]
+pub fn reports_every_error() {
+    let mut memory_map: MemoryMap = toml::from_str(
+        r#"name = "Faulty"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "level"
+type.unsigned = 8
+value = 200
+max = 100
+
+[[contains]]
+name = "mode"
+type.enum = { length = 2, map = { idle = 0, run = 1 } }
+value = "halt"
+
+[[contains]]
+name = "enable"
+type = "boolean"
+value = 1
+
+[[contains]]
+name = "after"
+type.unsigned = 8
+"#,
+    )
+    .expect("Failed to parse TOML");
+    let report = memory_map
+        .elaborate()
+        .expect_err("Elaborated an invalid map");
+    let errors: Vec<String> = report.errors().iter().map(|e| e.to_string()).collect();
+    assert_eq!(errors.len(), 3, "{errors:?}");
+    assert!(errors[0].contains("Field level value 200 is above its max of 100"));
+    assert!(errors[1].contains("Field mode value \"halt\" is not a variant of its enum"));
+    assert!(errors[2].contains("Field enable is a boolean and its value must be true or false"));
+    assert_eq!(report.to_string().lines().count(), 3);
+    // Fields following the faulty ones are still laid out
+    let json = serde_json::to_value(&memory_map).unwrap();
+    assert_eq!(json["contains"][3]["address"], 3);
+}
+
+#[test]
+pub fn errors_name_field_path() {
+    let err = elaborate_err(
+        r#"name = "Chip"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "control"
+type = "set"
+
+[[contains.contains]]
+name = "status"
+type = "set"
+
+[[contains.contains.contains]]
+name = "flags"
+type.unsigned = 4
+value = 20
+max = 15
+"#,
+    );
+    assert!(err.contains("Field control.status.flags value 20 is above its max of 15"));
+}
+
+fn elaboration_errors(contents: &str) -> Vec<ElaborationError> {
+    let mut memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    let report = memory_map
+        .elaborate()
+        .expect_err("Elaborated an invalid map");
+    report.into_errors()
+}
+
+#[test]
+pub fn error_variants() {
+    let errors = elaboration_errors(
+        r#"name = "Chip"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "level"
+type.unsigned = 8
+value = 200
+max = 100
+
+[[contains]]
+name = "enable"
+type = "boolean"
+reset = 1
+
+[[contains]]
+name = "Level"
+type.unsigned = 8
+
+[[contains]]
+name = "group"
+type = "set"
+"#,
+    );
+    assert!(
+        matches!(
+            &errors[..],
+            [
+                ElaborationError::DuplicateName { path, name },
+                ElaborationError::ValueOutOfRange {
+                    label: "value",
+                    bound: Bound::Max(100.0),
+                    ..
+                },
+                ElaborationError::TypeMismatch { label: "reset", .. },
+                ElaborationError::MissingContains { path: group },
+            ] if path == "Chip" && name == "Level" && group == "group"
+        ),
+        "{errors:?}"
+    );
+    let errors = elaboration_errors(
+        r#"name = "Wide"
+type.unsigned = 16
+address = 0xFFFF_FFFF_FFFF_FFFF
+
+[protocol]
+addressMax = 0xFFFF_FFFF_FFFF_FFFF
+dataMin = 1
+"#,
+    );
+    assert!(
+        matches!(
+            &errors[..],
+            [ElaborationError::AddressOverflow { path, address: u64::MAX }] if path == "Wide"
+        ),
+        "{errors:?}"
+    );
+}
+
+#[test]
+pub fn merge_errors() {
+    let map = |name: &str, data_min: u8| -> MemoryMap {
+        toml::from_str(&format!(
+            "name = \"{name}\"\ntype.unsigned = 8\n\n[protocol]\naddressMax = 0xFF\ndataMin = {data_min}\n"
+        ))
+        .expect("Failed to parse TOML")
+    };
+    let err = MemoryMap::merge("Empty", Vec::new())
+        .err()
+        .expect("Merged no maps");
+    assert!(matches!(&err, ElaborationError::NoMaps { name } if name == "Empty"));
+    let err = MemoryMap::merge("Both", vec![map("a", 1), map("b", 2)])
+        .err()
+        .expect("Merged conflicting protocols");
+    assert!(
+        matches!(&err, ElaborationError::ConflictingProtocol { name, first } if name == "b" && first == "a"),
+        "{err:?}"
+    );
+    MemoryMap::merge("Both", vec![map("a", 1), map("b", 1)]).expect("Failed to merge");
+}
+
+#[test]
+pub fn out_of_order_addresses() {
+    let json = elaborate(
+        r#"name = "Shuffled"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "high"
+address = 0x10
+type.unsigned = 32
+
+[[contains]]
+name = "low"
+address = 0x00
+type.unsigned = 32
+
+[[contains]]
+name = "packed"
+type.unsigned = 8
+"#,
+    );
+    assert_eq!(json["contains"][2]["address"], 4);
+    let err = elaborate_err(
+        r#"name = "Shuffled"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "high"
+address = 0x10
+type.unsigned = 32
+
+[[contains]]
+name = "low"
+address = 0x0E
+type.unsigned = 32
+"#,
+    );
+    assert!(
+        err.contains("Field high address 0x10 overlaps the preceding field, which ends at 0x11")
+    );
+}
+
+#[test]
+pub fn register_align() {
+    let contents = r#"name = "Registers"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+registerAlign = 4
+
+[[contains]]
+name = "byte"
+type.unsigned = 8
+
+[[contains]]
+name = "word"
+type.unsigned = 32
+
+[[contains]]
+name = "group"
+type = "set"
+contains = [
+    { name = "flag", type = "boolean" },
+    { name = "short", type.unsigned = 16 },
+]
+"#;
+    let json = elaborate(contents);
+    assert_eq!(json["protocol"]["registerAlign"], 4);
+    assert_eq!(json["contains"][0]["address"], 0x0);
+    assert_eq!(json["contains"][1]["address"], 0x4);
+    assert_eq!(json["contains"][2]["address"], 0x8);
+    assert_eq!(json["contains"][2]["contains"][0]["address"], 0x8);
+    assert_eq!(json["contains"][2]["contains"][1]["address"], 0xC);
+    let err =
+        elaborate_err(&contents.replace(r#"name = "word""#, "name = \"word\"\naddress = 0x6"));
+    assert!(err.contains("word address 0x6 is not aligned to the registerAlign of 4 bytes"));
+    let err = elaborate_err(&contents.replace("registerAlign = 4", "registerAlign = 0"));
+    assert!(err.contains("registerAlign of 0 bytes is not a nonzero multiple of dataMin"));
+}
+
+#[test]
+pub fn address_beyond_address_max() {
+    let contents = r#"name = "Edge"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "last"
+address = 0xFF
+type.string = 0
+"#;
+    let json = elaborate(contents);
+    assert_eq!(json["contains"][0]["address"], 0xFF);
+    let err = elaborate_err(&contents.replace("0xFF\ntype", "0x100\ntype"));
+    assert!(err.contains("last address 0x100 is beyond the maximum address 0xff"));
+}
+
+#[test]
+pub fn data_max_limits_field_width() {
+    let contents = r#"name = "Bus"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 4
+dataMax = 4
+
+[[contains]]
+name = "word"
+type.unsigned = 32
+
+[[contains]]
+name = "table"
+type.array = { count = 4, element.unsigned = 32 }
+
+[[contains]]
+name = "wide"
+type = "set"
+contains = [
+    { name = "low", type.unsigned = 32 },
+    { name = "high", type.unsigned = 32 },
+]
+"#;
+    elaborate(contents);
+    let err = elaborate_err(&contents.replace(
+        r#"name = "word"
+type.unsigned = 32"#,
+        r#"name = "word"
+type.unsigned = 64"#,
+    ));
+    assert!(err.contains(
+        "Field word is 8 bytes wide, more than the dataMax of 4 bytes; split it into a set"
+    ));
+}
+
+#[test]
+pub fn address_from_set_end() {
+    let contents = r#"name = "Device"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 4
+
+[[contains]]
+name = "block"
+type = "set"
+address = 0x10
+contains = [
+    { name = "version", type.unsigned = 32, address = -4, access = "r" },
+    { name = "control", type.unsigned = 32 },
+    { name = "status", type.unsigned = 32 },
+]
+
+[[contains]]
+name = "after"
+type.unsigned = 8
+"#;
+    let memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    let json = serde_json::to_value(&memory_map).expect("Failed to serialize to JSON value");
+    assert_eq!(json["contains"][0]["contains"][0]["address"], -4);
+
+    let json = elaborate(contents);
+    let block = &json["contains"][0];
+    assert_eq!(block["contains"][0]["address"], 0x18);
+    assert_eq!(block["contains"][0]["offset"], 0x8);
+    assert_eq!(block["contains"][1]["address"], 0x10);
+    assert_eq!(block["contains"][2]["address"], 0x14);
+    assert_eq!(block["range"], "0x10 .. 0x1b");
+    assert_eq!(json["contains"][1]["address"], 0x1C);
+
+    // The furthest offset sets the end, with nearer fields placed back from it
+    let json = elaborate(&contents.replace(
+        r#"{ name = "status", type.unsigned = 32 },"#,
+        r#"{ name = "status", type.unsigned = 32 },
+    { name = "serial", type.unsigned = 32, address = "-0xC" },"#,
+    ));
+    let block = &json["contains"][0];
+    assert_eq!(block["contains"][3]["address"], 0x18);
+    assert_eq!(block["contains"][0]["address"], 0x20);
+    assert_eq!(json["contains"][1]["address"], 0x24);
+
+    let err = elaborate_err(
+        r#"name = "Version"
+type.unsigned = 32
+address = -4
+
+[protocol]
+addressMax = 0xFF
+dataMin = 4
+"#,
+    );
+    assert!(err.contains("Field Version has a negative address but is not a member of a set"));
+}
+
+#[test]
+pub fn accessors_after_elaboration() {
+    let mut memory_map: MemoryMap = toml::from_str(
+        r#"name = "Device"
+type = "set"
+access = "r"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 2
+
+[[contains]]
+name = "control"
+type.unsigned = 16
+access = "rw"
+
+[[contains]]
+name = "status"
+type.unsigned = 8
+"#,
+    )
+    .expect("Failed to parse TOML");
+    assert_eq!(memory_map.root().children()[0].address(), None);
+    memory_map.elaborate().expect("Failed to elaborate");
+    assert_eq!(memory_map.protocol().address_max(), 0xFF);
+    assert_eq!(memory_map.protocol().data_min(), 2);
+    let root = memory_map.root();
+    assert_eq!(root.name(), "Device");
+    assert_eq!(root.range(), "0x0 .. 0x2");
+    let fields: Vec<_> = root
+        .children()
+        .iter()
+        .map(|field| {
+            (
+                field.path(),
+                field.address(),
+                field.access(),
+                field.field_type().to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            (
+                "control",
+                Some(0x0),
+                Some(Access::ReadWrite),
+                "unsigned(16)".to_string()
+            ),
+            (
+                "status",
+                Some(0x2),
+                Some(Access::Read),
+                "unsigned(8)".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+pub fn elaborate_twice() {
+    let mut memory_map: MemoryMap = toml::from_str(
+        r#"name = "Device"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 4
+
+[[contains]]
+name = "block"
+type = "set"
+address = 0x10
+contains = [
+    { name = "version", type.unsigned = 32, address = -4, access = "r" },
+    { name = "control", type.unsigned = 32 },
+    { name = "status", type.unsigned = 32 },
+]
+
+[[contains]]
+name = "after"
+type.unsigned = 8
+"#,
+    )
+    .expect("Failed to parse TOML");
+    memory_map.elaborate().expect("Failed to elaborate");
+    let first = memory_map.to_json().expect("Failed to serialize");
+    memory_map.elaborate().expect("Failed to elaborate again");
+    assert_eq!(memory_map.to_json().expect("Failed to serialize"), first);
+    let addresses: Vec<_> = memory_map
+        .flatten()
+        .into_iter()
+        .map(|field| (field.path, field.address))
+        .collect();
+    assert_eq!(
+        addresses,
+        [
+            ("block.control".to_string(), 0x10),
+            ("block.status".to_string(), 0x14),
+            ("block.version".to_string(), 0x18),
+            ("after".to_string(), 0x1C),
+        ]
+    );
+}
+
+#[test]
+pub fn unit_inherited_from_set() {
+    let json = elaborate(
+        r#"name = "Supplies"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "rails"
+type = "set"
+unit = "mV"
+contains = [
+    { name = "core", type.unsigned = 16 },
+    { name = "io", type.unsigned = 16, unit = "V" },
+    { name = "good", type = "boolean" },
+]
+"#,
+    );
+    let rails = &json["contains"][0]["contains"];
+    assert_eq!(rails[0]["unit"], "mV");
+    assert_eq!(rails[1]["unit"], "V");
+    assert_eq!(rails[2].get("unit"), None);
+}
+
+#[test]
+pub fn strict_requires_set_root() {
+    let contents = r#"name = "Counter"
+type.unsigned = 32
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#;
+    let mut memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    memory_map.elaborate().expect("Failed to elaborate");
+    memory_map.set_strict(true);
+    let errors = memory_map
+        .elaborate()
+        .expect_err("Elaborated a scalar root under the strict flag")
+        .into_errors();
+    assert!(
+        matches!(
+            errors.as_slice(),
+            [ElaborationError::RootNotSet { name, field_type }]
+                if name == "Counter" && field_type == "unsigned(32)"
+        ),
+        "{errors:?}"
+    );
+
+    let mut memory_map: MemoryMap = toml::from_str(&contents.replace(
+        "type.unsigned = 32",
+        "type = \"set\"\ncontains = { name = \"Counter\", type.unsigned = 32 }",
+    ))
+    .expect("Failed to parse TOML");
+    memory_map.set_strict(true);
+    memory_map
+        .elaborate()
+        .expect("Failed to elaborate a set root");
+}
+
+#[test]
+pub fn bit_packing() {
+    let mut contents = String::from(
+        r#"name = "Flags"
+type = "set"
+packBits = true
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#,
+    );
+    for bit in 0..8 {
+        contents.push_str(&format!(
+            "\n[[contains]]\nname = \"flag{bit}\"\ntype.unsigned = 1\n"
+        ));
+    }
+    contents.push_str("\n[[contains]]\nname = \"after\"\ntype.unsigned = 8\n");
+    let json = elaborate(&contents);
+    let members = json["contains"].as_array().expect("Members not an array");
+    for (bit, flag) in members[..8].iter().enumerate() {
+        assert_eq!(flag["address"], 0, "{flag}");
+        assert_eq!(flag["bitOffset"], bit, "{flag}");
+    }
+    assert_eq!(members[8]["address"], 1);
+    assert_eq!(members[8].get("bitOffset"), None);
+
+    // Without packBits every flag takes a byte of its own
+    let json = elaborate(&contents.replace("packBits = true\n", ""));
+    assert_eq!(json["contains"][7]["address"], 7);
+    assert_eq!(json["contains"][8]["address"], 8);
+}
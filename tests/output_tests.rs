@@ -0,0 +1,29 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use vhdl_doc::output::{stream_if_changed, write_if_changed};
+
+#[test]
+pub fn skips_identical_content() {
+    let path = env::temp_dir().join("vhdl_doc_write_if_changed.txt");
+    let _ = fs::remove_file(&path);
+    assert!(write_if_changed(&path, b"first").expect("Failed to write file"));
+    assert!(!write_if_changed(&path, b"first").expect("Failed to write file"));
+    assert!(write_if_changed(&path, b"second").expect("Failed to write file"));
+    assert_eq!(fs::read(&path).expect("Failed to read file"), b"second");
+    fs::remove_file(&path).expect("Failed to remove file");
+}
+
+#[test]
+pub fn streams_only_changed_content() {
+    let path = env::temp_dir().join("vhdl_doc_stream_if_changed.txt");
+    let _ = fs::remove_file(&path);
+    let stream = |content: &'static [u8]| {
+        stream_if_changed(&path, |out| out.write_all(content)).expect("Failed to write file")
+    };
+    assert!(stream(b"first"));
+    assert!(!stream(b"first"));
+    assert!(stream(b"second"));
+    assert_eq!(fs::read(&path).expect("Failed to read file"), b"second");
+    fs::remove_file(&path).expect("Failed to remove file");
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "rayon")]
+
+use serde_json::{Value, json};
+use vhdl_doc::memory_map::schema::MemoryMap;
+
+/// Builds a map of `sets` explicitly addressed sets, each holding `fields` auto-packed members
+/// of assorted types. When `faulty` is set, every hundredth member has an out of range value.
+fn large_map(sets: u64, fields: u64, faulty: bool) -> Value {
+    let contains: Vec<Value> = (0..sets)
+        .map(|set| {
+            let members: Vec<Value> = (0..fields)
+                .map(|field| {
+                    let name = format!("field{field}");
+                    match field % 4 {
+                        0 => json!({ "name": name, "type": { "unsigned": 32 } }),
+                        1 => json!({ "name": name, "type": "boolean", "value": true }),
+                        2 => json!({
+                            "name": name,
+                            "type": { "enum": { "length": 2, "map": { "idle": 0, "run": 1 } } },
+                        }),
+                        _ => json!({
+                            "name": name,
+                            "type": { "unsigned": 8 },
+                            "value": field,
+                            "max": if faulty && field % 100 == 3 { 1 } else { 255 },
+                        }),
+                    }
+                })
+                .collect();
+            json!({
+                "name": format!("block{set}"),
+                "type": "set",
+                "address": set * 0x1000,
+                "contains": members,
+            })
+        })
+        .collect();
+    json!({
+        "name": "Large",
+        "type": "set",
+        "protocol": { "addressMax": sets * 0x1000, "dataMin": 4 },
+        "contains": contains,
+    })
+}
+
+#[test]
+pub fn parallel_matches_serial() {
+    let map = large_map(64, 128, false);
+    let mut serial: MemoryMap = serde_json::from_value(map.clone()).unwrap();
+    let mut parallel: MemoryMap = serde_json::from_value(map).unwrap();
+    serial.elaborate().expect("Failed to elaborate");
+    parallel.elaborate_parallel().expect("Failed to elaborate");
+    assert_eq!(
+        serde_json::to_value(&serial).unwrap(),
+        serde_json::to_value(&parallel).unwrap()
+    );
+    assert_eq!(serial.to_markdown(), parallel.to_markdown());
+}
+
+#[test]
+pub fn parallel_reports_same_errors() {
+    let map = large_map(16, 256, true);
+    let mut serial: MemoryMap = serde_json::from_value(map.clone()).unwrap();
+    let mut parallel: MemoryMap = serde_json::from_value(map).unwrap();
+    let serial = serial.elaborate().expect_err("Elaborated an invalid map");
+    let parallel = parallel
+        .elaborate_parallel()
+        .expect_err("Elaborated an invalid map");
+    assert_eq!(serial.errors().len(), 16 * 3);
+    assert_eq!(serial.to_string(), parallel.to_string());
+}
@@ -0,0 +1,1040 @@
+use std::fs;
+use std::path::Path;
+use vhdl_doc::memory_map::schema::{
+    Access, AddressRange, ElaborationError, Field, FieldBuilder, MemoryMap, MemoryMapBuilder,
+    Protocol, Value, html_anchor,
+};
+use vhdl_lang::{Diagnostic, Source, VHDLParser, VHDLStandard};
+
+fn elaborate(contents: &str) -> MemoryMap {
+    let mut memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    memory_map.elaborate().expect("Failed to elaborate");
+    memory_map
+}
+
+fn sample_map() -> MemoryMap {
+    let contents = fs::read_to_string("tests/assets/memory_map.json").expect("Failed to read file");
+    let mut memory_map: MemoryMap = serde_json::from_str(&contents).expect("Failed to parse JSON");
+    memory_map.elaborate().expect("Failed to elaborate");
+    memory_map
+}
+
+#[test]
+pub fn markdown_golden() {
+    let expected = fs::read_to_string("tests/assets/memory_map.md").expect("Failed to read file");
+    assert_eq!(sample_map().to_markdown(), expected);
+}
+
+/// Parses VHDL-2008 source, returning any syntax diagnostics
+fn vhdl_diagnostics(vhdl: &str) -> Vec<Diagnostic> {
+    let parser = VHDLParser::new(VHDLStandard::VHDL2008);
+    let mut diagnostics = Vec::new();
+    parser.parse_design_source(
+        &Source::inline(Path::new("generated.vhd"), vhdl),
+        &mut diagnostics,
+    );
+    diagnostics
+}
+
+#[test]
+pub fn vhdl_golden() {
+    let expected = fs::read_to_string("tests/assets/memory_map.vhd").expect("Failed to read file");
+    assert_eq!(sample_map().to_vhdl_package("memory_map_pkg"), expected);
+    assert!(vhdl_diagnostics(&expected).is_empty());
+}
+
+#[test]
+pub fn vhdl_enum_type() {
+    let memory_map = elaborate(
+        r#"name = "Controller"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "Control"
+type = "set"
+address = 0x10
+
+[[contains.contains]]
+name = "Mode"
+type.enum.length = 2
+type.enum.map = { run = 1, idle = 0, halt = 3 }
+"#,
+    );
+    let vhdl = memory_map.to_vhdl_package("controller_pkg");
+    assert!(vhdl_diagnostics(&vhdl).is_empty());
+    assert!(vhdl.contains("constant ADDRESS_WIDTH : natural := 8;"));
+    assert!(vhdl.contains(
+        "constant CONTROL_MODE_ADDR : unsigned(ADDRESS_WIDTH - 1 downto 0) := 8x\"10\";"
+    ));
+    assert!(vhdl.contains(
+        "type CONTROL_MODE_T is (CONTROL_MODE_IDLE, CONTROL_MODE_RUN, CONTROL_MODE_HALT);"
+    ));
+    assert!(vhdl.contains("constant CONTROL_MODE_HALT_CODE : unsigned(1 downto 0) := 2d\"3\";"));
+}
+
+#[test]
+pub fn vhdl_rejects_empty_enum() {
+    let contents = r#"name = "Controller"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "Mode"
+type.enum.length = 0
+type.enum.map = {}
+"#;
+    let mut memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    let err = memory_map
+        .elaborate()
+        .expect_err("Elaborated an enum without variants");
+    assert!(
+        err.to_string()
+            .contains("Field Mode is an enum without any variants"),
+        "{err}"
+    );
+
+    let memory_map = elaborate(&contents.replace(
+        "type.enum.length = 0\ntype.enum.map = {}",
+        "type.enum.length = 1\ntype.enum.map = { only = 0 }",
+    ));
+    let vhdl = memory_map.to_vhdl_package("controller_pkg");
+    assert!(vhdl_diagnostics(&vhdl).is_empty(), "{vhdl}");
+    assert!(vhdl.contains("type MODE_T is (MODE_ONLY);"));
+}
+
+#[test]
+pub fn c_header_golden() {
+    let expected = fs::read_to_string("tests/assets/memory_map.h").expect("Failed to read file");
+    assert_eq!(sample_map().to_c_header("MEMORY_MAP_H"), expected);
+}
+
+#[test]
+pub fn c_header_overlay() {
+    let memory_map = elaborate(
+        r#"name = "Peripheral"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "ctrl"
+type.unsigned = 32
+
+[[contains]]
+name = "mode"
+address = 0x8
+type.enum.length = 8
+type.enum.map = { idle = 0, run = 1 }
+
+[[contains]]
+name = "odd"
+type.unsigned = 24
+
+[[contains]]
+name = "fifo"
+address = 0x10
+type.array = { count = 4, element.unsigned = 16 }
+"#,
+    );
+    let header = memory_map.to_c_header("PERIPHERAL_H");
+    assert!(header.starts_with("/* Peripheral register definitions generated by vhdl_doc */\n#ifndef PERIPHERAL_H\n#define PERIPHERAL_H\n"));
+    assert!(header.contains("#define MODE_ADDR 0x08u\n"));
+    assert!(header.contains("#define MODE_RUN 1u\n"));
+    assert!(header.contains(
+        "typedef struct {
+    uint32_t ctrl;
+    uint8_t reserved0[4];
+    uint8_t mode;
+    uint8_t odd[3];
+    uint8_t reserved1[4];
+    uint16_t fifo[4];
+} peripheral_t;
+"
+    ));
+    assert!(header.ends_with("#endif /* PERIPHERAL_H */\n"));
+}
+
+#[test]
+pub fn c_header_signed_members() {
+    let memory_map = elaborate(
+        r#"name = "Sensor"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "temperature"
+type.sfixed = { high = 11, low = -4 }
+
+[[contains]]
+name = "offset"
+type.signed = 8
+
+[[contains]]
+name = "samples"
+address = 0x4
+type.array = { count = 2, element.signed = 32 }
+
+[[contains]]
+name = "count"
+type.unsigned = 32
+"#,
+    );
+    assert!(memory_map.to_c_header("SENSOR_H").contains(
+        "typedef struct {
+    int16_t temperature;
+    int8_t offset;
+    uint8_t reserved0[1];
+    int32_t samples[2];
+    uint32_t count;
+} sensor_t;
+"
+    ));
+}
+
+#[test]
+pub fn svg_bitfield_labels() {
+    let field: Field = toml::from_str(
+        r#"name = "Status"
+type.bitfield.length = 3
+type.bitfield.bits = ["ready", "error", "busy"]
+"#,
+    )
+    .expect("Failed to parse TOML");
+    let protocol: Protocol =
+        toml::from_str("addressMax = 0xFF\ndataMin = 1").expect("Failed to parse TOML");
+    let svg = field.to_svg(&protocol);
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg""#));
+    assert!(svg.ends_with("</svg>\n"));
+    for label in ["ready", "error", "busy"] {
+        assert!(svg.contains(&format!(">{label}</text>")));
+    }
+    // Bits 3 to 7 pad the field to data_min and are hatched
+    assert_eq!(svg.matches(r#"fill="url(#reserved)""#).count(), 5);
+}
+
+#[test]
+pub fn svg_pattern_id_leaves_labels_intact() {
+    let field: Field = toml::from_str(
+        r#"name = "Status"
+type.bitfield.length = 2
+type.bitfield.bits = ["reserved", "busy"]
+"#,
+    )
+    .expect("Failed to parse TOML");
+    let protocol: Protocol =
+        toml::from_str("addressMax = 0xFF\ndataMin = 1").expect("Failed to parse TOML");
+    let svg = field.to_svg_with_pattern(&protocol, "reserved-status");
+    assert!(svg.contains(r#"<pattern id="reserved-status" "#));
+    assert_eq!(svg.matches(r#"fill="url(#reserved-status)""#).count(), 6);
+    assert!(svg.contains(">reserved</text>"));
+    assert!(!svg.contains("url(#reserved)"));
+}
+
+#[test]
+pub fn byte_lanes_follow_endianness() {
+    let field: Field = toml::from_str("name = \"Word\"\ntype.unsigned = 32").unwrap();
+    let little: Protocol =
+        toml::from_str("addressMax = 0xFF\ndataMin = 1").expect("Failed to parse TOML");
+    let big: Protocol = toml::from_str("addressMax = 0xFF\ndataMin = 1\nendianness = \"big\"")
+        .expect("Failed to parse TOML");
+    let offsets = |protocol: &Protocol| -> Vec<(u64, u64, u64)> {
+        field
+            .byte_lanes(protocol)
+            .iter()
+            .map(|lane| (lane.offset, lane.high, lane.low))
+            .collect()
+    };
+    assert_eq!(
+        offsets(&little),
+        [(0, 7, 0), (1, 15, 8), (2, 23, 16), (3, 31, 24)]
+    );
+    assert_eq!(
+        offsets(&big),
+        [(3, 7, 0), (2, 15, 8), (1, 23, 16), (0, 31, 24)]
+    );
+    // The most significant byte is drawn leftmost
+    assert!(
+        field
+            .to_svg(&big)
+            .contains(r#"<text x="132" y="68">+0</text>"#)
+    );
+    assert!(
+        field
+            .to_svg(&little)
+            .contains(r#"<text x="132" y="68">+3</text>"#)
+    );
+}
+
+#[test]
+pub fn markdown_reset_and_default() {
+    let markdown = elaborate(
+        r#"name = "Timer"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "period"
+type.unsigned = 16
+value = 1000
+reset = 0
+
+[[contains]]
+name = "prescale"
+type.unsigned = 8
+value = 4
+"#,
+    )
+    .to_markdown();
+    assert!(markdown.contains("| 0x00 | period | unsigned(16) | rw | 0 | 1000 |"));
+    assert!(markdown.contains("| 0x02 | prescale | unsigned(8) | rw | 4 | 4 |"));
+}
+
+#[test]
+pub fn side_effect_access_annotations() {
+    let memory_map = elaborate(
+        r#"name = "Block"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "irq"
+access = "w1c"
+type.bitfield = { length = 2, bits = ["rx", "tx"] }
+
+[[contains]]
+name = "start"
+access = "strobe"
+type = "boolean"
+"#,
+    );
+    let header = memory_map.to_c_header("BLOCK_H");
+    assert!(header.contains("#define IRQ_ADDR 0x00u /* write 1 to clear */\n"));
+    assert!(header.contains("#define START_ADDR 0x01u /* write strobe */\n"));
+    let vhdl = memory_map.to_vhdl_package("block_pkg");
+    assert!(vhdl.contains(":= 8x\"0\"; -- write 1 to clear\n"));
+    assert!(vhdl_diagnostics(&vhdl).is_empty());
+    let markdown = memory_map.to_markdown();
+    assert!(markdown.contains("| 0x01 | start | boolean | strobe |"));
+}
+
+#[test]
+pub fn address_report_sample() {
+    let report = sample_map().address_report();
+    assert!(report.gaps.is_empty());
+    assert_eq!(report.used_bytes, 0x17);
+    assert_eq!(report.free_bytes, 0x1_0000_0000 - 0x17);
+}
+
+#[test]
+pub fn address_report_gaps() {
+    let report = elaborate(
+        r#"name = "Sparse"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 4
+
+[[contains]]
+name = "ctrl"
+address = 0x04
+type.unsigned = 16
+
+[[contains]]
+name = "stat"
+type.unsigned = 32
+
+[[contains]]
+name = "data"
+address = 0x20
+type.array = { count = 2, element.unsigned = 32 }
+"#,
+    )
+    .address_report();
+    assert_eq!(
+        report.gaps,
+        [
+            AddressRange {
+                start: 0x00,
+                end: 0x03
+            },
+            AddressRange {
+                start: 0x06,
+                end: 0x07
+            },
+            AddressRange {
+                start: 0x0c,
+                end: 0x1f
+            },
+        ]
+    );
+    assert_eq!(report.used_bytes, 14);
+    assert_eq!(report.free_bytes, 256 - 14);
+    assert_eq!(
+        report.to_string(),
+        "14 bytes used, 242 bytes free\nGaps:\n  0x0 .. 0x3 (4 bytes)\n  0x6 .. 0x7 (2 bytes)\n  0xc .. 0x1f (20 bytes)\n"
+    );
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["usedBytes"], 14);
+    assert_eq!(json["gaps"][2]["end"], 0x1f);
+}
+
+#[test]
+pub fn size_bytes_sample() {
+    let memory_map = sample_map();
+    // string(20) + bitfield(16) + bitfield(8)
+    assert_eq!(memory_map.size_bytes(), 20 + 2 + 1);
+    let unelaborated: MemoryMap = serde_json::from_str(
+        &fs::read_to_string("tests/assets/memory_map.json").expect("Failed to read file"),
+    )
+    .expect("Failed to parse JSON");
+    assert_eq!(unelaborated.size_bytes(), 0);
+}
+
+#[test]
+pub fn size_bytes_subtree() {
+    let protocol: Protocol =
+        toml::from_str("addressMax = 0xFF\ndataMin = 4").expect("Failed to parse TOML");
+    let memory_map = elaborate(
+        r#"name = "Block"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 4
+
+[[contains]]
+name = "header"
+type.unsigned = 8
+
+[[contains]]
+name = "group"
+type = "set"
+contains = [
+    { name = "a", type.unsigned = 32 },
+    { name = "b", type.unsigned = 16 },
+]
+"#,
+    );
+    let json = serde_json::to_value(&memory_map).unwrap();
+    let group: Field = serde_json::from_value(json["contains"][1].clone()).unwrap();
+    // a occupies 0x04..0x07 and b 0x08..0x09
+    assert_eq!(group.size_bytes(&protocol), 6);
+    assert_eq!(memory_map.size_bytes(), 10);
+}
+
+#[test]
+pub fn size_bytes_before_elaboration() {
+    let protocol: Protocol =
+        toml::from_str("addressMax = 0xFF\ndataMin = 1").expect("Failed to parse TOML");
+    let field = |address: &str| -> Field {
+        toml::from_str(&format!(
+            "name = \"wide\"\ntype.unsigned = 32\naddress = {address}"
+        ))
+        .expect("Failed to parse TOML")
+    };
+    assert_eq!(field("0x10").size_bytes(&protocol), 4);
+    // The end of a field at the top of the address space cannot be represented
+    assert_eq!(field("0xFFFF_FFFF_FFFF_FFFE").size_bytes(&protocol), 0);
+}
+
+#[test]
+pub fn flatten_orders_by_address() {
+    let flat = elaborate(
+        r#"name = "Chip"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "late"
+type = "set"
+address = 0x40
+access = "r"
+
+[[contains.contains]]
+name = "status"
+type.unsigned = 16
+
+[[contains.contains]]
+name = "inner"
+type = "set"
+contains = { name = "flags", type.bitfield = { length = 2, bits = ["a", "b"] } }
+
+[[contains]]
+name = "control"
+type = "set"
+address = 0x10
+contains = [
+    { name = "enable", type = "boolean" },
+    { name = "mode", type.enum = { length = 2, map = { idle = 0, run = 1 } } },
+]
+"#,
+    )
+    .flatten();
+    let entries: Vec<(&str, u64, u64, Access)> = flat
+        .iter()
+        .map(|field| {
+            (
+                field.path.as_str(),
+                field.address,
+                field.bytes,
+                field.access,
+            )
+        })
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            ("control.enable", 0x10, 1, Access::ReadWrite),
+            ("control.mode", 0x11, 1, Access::ReadWrite),
+            ("late.status", 0x40, 2, Access::Read),
+            ("late.inner.flags", 0x42, 1, Access::Read),
+        ]
+    );
+    assert_eq!(flat[1].range, "0=idle, 1=run");
+}
+
+#[test]
+pub fn writers_match_strings() {
+    let memory_map = sample_map();
+    let mut sink = Vec::new();
+    memory_map
+        .write_markdown(&mut sink)
+        .expect("Failed to write Markdown");
+    assert_eq!(String::from_utf8(sink).unwrap(), memory_map.to_markdown());
+    let mut sink = Vec::new();
+    memory_map
+        .write_json(&mut sink)
+        .expect("Failed to write JSON");
+    assert_eq!(
+        String::from_utf8(sink).unwrap(),
+        memory_map.to_json().unwrap()
+    );
+    let mut sink = Vec::new();
+    memory_map
+        .write_vhdl_package("memory_map_pkg", &mut sink)
+        .expect("Failed to write VHDL");
+    assert_eq!(
+        String::from_utf8(sink).unwrap(),
+        memory_map.to_vhdl_package("memory_map_pkg")
+    );
+    let mut sink = Vec::new();
+    memory_map
+        .write_c_header("MEMORY_MAP_H", &mut sink)
+        .expect("Failed to write C header");
+    assert_eq!(
+        String::from_utf8(sink).unwrap(),
+        memory_map.to_c_header("MEMORY_MAP_H")
+    );
+}
+
+#[test]
+pub fn shared_enum_declared_once() {
+    let memory_map = elaborate(
+        r#"name = "Channels"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "Rx Mode"
+type.enum.length = 2
+type.enum.map = { idle = 0, run = 1 }
+
+[[contains]]
+name = "Tx Mode"
+type.enum.length = 2
+type.enum.map = { run = 1, idle = 0 }
+
+[[contains]]
+name = "Rx Flags"
+type.bitfield = { length = 2, bits = ["ready", "error"] }
+
+[[contains]]
+name = "Tx Flags"
+type.bitfield = { length = 2, bits = { error = 1, ready = 0 } }
+"#,
+    );
+    let vhdl = memory_map.to_vhdl_package("channels_pkg");
+    assert!(vhdl_diagnostics(&vhdl).is_empty());
+    let declarations = vhdl
+        .lines()
+        .filter(|line| line.trim_start().starts_with("type "))
+        .count();
+    assert_eq!(declarations, 1);
+    assert!(vhdl.contains("type RX_MODE_T is (RX_MODE_IDLE, RX_MODE_RUN);"));
+    assert!(vhdl.contains("subtype TX_MODE_T is RX_MODE_T;"));
+    assert!(!vhdl.contains("TX_MODE_RUN"));
+    let header = memory_map.to_c_header("CHANNELS_H");
+    assert!(header.contains("#define RX_MODE_RUN 1u"));
+    assert!(!header.contains("TX_MODE_RUN"));
+    assert!(header.contains("/* TX_MODE uses the RX_MODE definitions */"));
+    assert!(header.contains("#define RX_FLAGS_ERROR_MASK"));
+    assert!(!header.contains("TX_FLAGS_ERROR"));
+    assert!(header.contains("/* TX_FLAGS uses the RX_FLAGS definitions */"));
+}
+
+#[test]
+pub fn offset_within_set() {
+    let memory_map = elaborate(
+        r#"name = "Chip"
+type = "set"
+
+[protocol]
+addressMax = 0xFFF
+dataMin = 4
+
+[[contains]]
+name = "block"
+type = "set"
+address = 0x100
+contains = [
+    { name = "control", type.unsigned = 32 },
+    { name = "status", type.unsigned = 16 },
+    { name = "inner", type = "set", contains = { name = "flags", type = "boolean" } },
+]
+"#,
+    );
+    let json = serde_json::to_value(&memory_map).unwrap();
+    let block = &json["contains"][0];
+    assert_eq!(block["offset"], 0x100);
+    assert_eq!(block["contains"][1]["address"], 0x104);
+    assert_eq!(block["contains"][1]["offset"], 4);
+    assert_eq!(block["contains"][2]["offset"], 8);
+    assert_eq!(block["contains"][2]["contains"]["offset"], 0);
+    assert!(json.get("offset").is_none());
+    let flat = memory_map.flatten();
+    assert_eq!(flat[1].path, "block.status");
+    assert_eq!(flat[1].offset, Some(4));
+}
+
+#[test]
+pub fn ipxact_sample() {
+    let xml = sample_map().to_ipxact("example");
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+    assert!(xml.contains(
+        "<ipxact:component xmlns:ipxact=\"http://www.accellera.org/XMLSchema/IPXACT/1685-2014\">"
+    ));
+    assert!(
+        xml.contains(
+            "  <ipxact:name>example</ipxact:name>\n  <ipxact:version>1.0</ipxact:version>"
+        )
+    );
+    assert!(xml.contains("<ipxact:range>4294967296</ipxact:range>"));
+    assert!(
+        xml.contains("<ipxact:registerFile>\n          <ipxact:name>A_NICE_GROUP</ipxact:name>")
+    );
+    assert!(xml.contains(
+        "<ipxact:name>A_BITFIELD</ipxact:name>
+            <ipxact:displayName>A bitfield</ipxact:displayName>
+            <ipxact:addressOffset>'h14</ipxact:addressOffset>
+            <ipxact:size>16</ipxact:size>"
+    ));
+    assert!(xml.contains(
+        "<ipxact:name>TEN</ipxact:name>
+              <ipxact:bitOffset>10</ipxact:bitOffset>
+              <ipxact:bitWidth>1</ipxact:bitWidth>
+              <ipxact:access>read-write</ipxact:access>"
+    ));
+    assert!(xml.contains("<ipxact:bitWidth>160</ipxact:bitWidth>\n              <ipxact:access>read-only</ipxact:access>"));
+    assert!(xml.ends_with("</ipxact:memoryMaps>\n</ipxact:component>\n"));
+}
+
+#[test]
+pub fn ipxact_resets_enums_and_side_effects() {
+    let xml = elaborate(
+        r#"name = "Controller"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 4
+
+[[contains]]
+name = "Mode"
+type.enum = { length = 2, map = { idle = 0, run = 1, halt = 2 } }
+reset = "halt"
+
+[[contains]]
+name = "Offset"
+type.signed = 8
+value = -2
+
+[[contains]]
+name = "Irq"
+access = "w1c"
+type.bitfield = { length = 8, bits = { rx = 0, tx = 4 } }
+reset = "0x10"
+
+[[contains]]
+name = "Table"
+type.array = { count = 4, element.unsigned = 32 }
+"#,
+    )
+    .to_ipxact("controller");
+    // Compare element sequences regardless of indentation
+    let xml = xml.lines().map(str::trim).collect::<Vec<_>>().join("");
+    assert!(xml.contains(
+        "<ipxact:name>MODE</ipxact:name><ipxact:bitOffset>0</ipxact:bitOffset>\
+         <ipxact:resets><ipxact:reset><ipxact:value>'h2</ipxact:value></ipxact:reset></ipxact:resets>"
+    ));
+    assert!(xml.contains(
+        "<ipxact:enumeratedValue><ipxact:name>HALT</ipxact:name><ipxact:value>2</ipxact:value>"
+    ));
+    assert!(xml.contains("<ipxact:value>'hFE</ipxact:value>"));
+    assert!(xml.contains(
+        "<ipxact:name>TX</ipxact:name><ipxact:bitOffset>4</ipxact:bitOffset><ipxact:resets>\
+         <ipxact:reset><ipxact:value>'h1</ipxact:value>"
+    ));
+    assert!(xml.contains(
+        "<ipxact:access>read-write</ipxact:access>\
+         <ipxact:modifiedWriteValue>oneToClear</ipxact:modifiedWriteValue>"
+    ));
+    assert!(xml.contains(
+        "<ipxact:dim>4</ipxact:dim><ipxact:addressOffset>'hC</ipxact:addressOffset>\
+         <ipxact:size>32</ipxact:size>"
+    ));
+}
+
+/// Splits CSV text into rows of cells, undoing RFC 4180 quoting
+fn parse_csv(csv: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.next_if_eq(&'"').is_some() => cell.push('"'),
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut cell)),
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => cell.push(c),
+        }
+    }
+    rows
+}
+
+#[test]
+pub fn csv_rows_match_leaves() {
+    let memory_map = sample_map();
+    let rows = parse_csv(&memory_map.to_csv());
+    assert_eq!(
+        rows[0],
+        [
+            "path",
+            "address",
+            "bits",
+            "access",
+            "reset",
+            "unit",
+            "range",
+            "description"
+        ]
+    );
+    assert_eq!(rows.len() - 1, memory_map.flatten().len());
+    assert!(rows.iter().all(|row| row.len() == 8));
+
+    let memory_map = elaborate(
+        r#"name = "Quoting"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "mode"
+description = "Mode, either \"fast\" or \"slow\"\nwith a second line"
+type = "boolean"
+reset = true
+
+[[contains]]
+name = "group"
+type = "set"
+
+[[contains.contains]]
+name = "level"
+type.unsigned = 12
+unit = "mV"
+"#,
+    );
+    let csv = memory_map.to_csv();
+    assert!(csv.contains(
+        "mode,0x00,1,rw,true,,false | true,\"Mode, either \"\"fast\"\" or \"\"slow\"\"\nwith"
+    ));
+    let rows = parse_csv(&csv);
+    assert_eq!(rows.len(), 3);
+    assert_eq!(
+        rows[1][7],
+        "Mode, either \"fast\" or \"slow\"\nwith a second line"
+    );
+    assert_eq!(
+        rows[2],
+        ["group.level", "0x01", "12", "rw", "", "mV", "0 .. 4095", ""]
+    );
+}
+
+#[test]
+pub fn html_anchors_for_every_leaf() {
+    let memory_map = sample_map();
+    let html = memory_map.to_html("Example <registers>");
+    assert!(html.starts_with("<!DOCTYPE html>\n<html lang=\"en\">\n"));
+    assert!(html.contains("<title>Example &lt;registers&gt;</title>"));
+    let leaves = memory_map.flatten();
+    assert!(!leaves.is_empty());
+    for field in leaves {
+        let anchor = html_anchor(&field.path);
+        assert!(!anchor.contains(char::is_whitespace));
+        assert!(html.contains(&format!("<section id=\"{anchor}\">")));
+        assert!(html.contains(&format!("<a href=\"#{anchor}\">")));
+    }
+    assert!(html.contains("<svg "));
+    assert!(!html.contains("id=\"reserved\""));
+    assert!(html.ends_with("</body>\n</html>\n"));
+}
+
+#[test]
+pub fn builder_matches_sample() {
+    let memory_map = MemoryMapBuilder::new("Example Memory Map")
+        .protocol(0xFFFF_FFFF, 1)
+        .protocol_name("Example Protocol")
+        .field(
+            FieldBuilder::new("A nice group")
+                .at(0x0000)
+                .access(Access::Read)
+                .field(
+                    FieldBuilder::new("Description String")
+                        .string(20)
+                        .value(Value::String("My Great Memory Map".to_string())),
+                )
+                .field(
+                    FieldBuilder::new("A bitfield")
+                        .access(Access::ReadWrite)
+                        .bitfield(16, [("zero", 0), ("one", 1), ("two", 2), ("ten", 10)]),
+                )
+                .field(
+                    FieldBuilder::new("Another bitfield")
+                        .access(Access::ReadWrite)
+                        .bits(8, ["item0", "item1", "item2", "item3"]),
+                ),
+        )
+        .build()
+        .expect("Failed to build");
+    // The builder takes addresses as numbers, which serialize as such rather than as written
+    let expected = sample_map()
+        .to_json()
+        .expect("Failed to serialize")
+        .replace("\"address\": \"0x0000\"", "\"address\": 0");
+    assert_eq!(memory_map.to_json().expect("Failed to serialize"), expected);
+}
+
+#[test]
+pub fn builder_requires_protocol() {
+    let err = MemoryMapBuilder::new("Registers")
+        .field(FieldBuilder::new("Control").unsigned(32))
+        .build()
+        .err()
+        .expect("Built a memory map without a protocol");
+    assert!(matches!(err.errors(), [ElaborationError::MissingProtocol]));
+    assert_eq!(err.to_string(), "No protocol was given for the memory map");
+}
+
+#[test]
+pub fn reserved_space_between_registers() {
+    let memory_map = elaborate(
+        r#"name = "Device"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 4
+
+[[contains]]
+name = "control"
+type.unsigned = 32
+
+[[contains]]
+name = "gap"
+type.reserved = 8
+access = "rw"
+
+[[contains]]
+name = "status"
+type.unsigned = 32
+"#,
+    );
+    let fields: Vec<_> = memory_map
+        .flatten()
+        .into_iter()
+        .map(|field| {
+            (
+                field.path,
+                field.address,
+                field.bytes,
+                field.access,
+                field.range,
+            )
+        })
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            (
+                "control".to_string(),
+                0x0,
+                4,
+                Access::ReadWrite,
+                "0 .. 4294967295".to_string()
+            ),
+            (
+                "gap".to_string(),
+                0x4,
+                8,
+                Access::Read,
+                "reserved".to_string()
+            ),
+            (
+                "status".to_string(),
+                0xC,
+                4,
+                Access::ReadWrite,
+                "0 .. 4294967295".to_string()
+            ),
+        ]
+    );
+    assert!(
+        memory_map
+            .to_markdown()
+            .contains("| 0x04 | gap | reserved(8) | r |  |  | reserved |  |")
+    );
+    assert!(!memory_map.to_vhdl_package("device_pkg").contains("GAP"));
+}
+
+#[test]
+pub fn relocate_sample_to_base() {
+    let contents = fs::read_to_string("tests/assets/memory_map.json").expect("Failed to read file");
+    let relocated = contents.replace(
+        "\"dataMin\": 1",
+        "\"dataMin\": 1,\n        \"baseAddress\": \"0x4000\"",
+    );
+    let mut memory_map: MemoryMap = serde_json::from_str(&relocated).expect("Failed to parse JSON");
+    memory_map.elaborate().expect("Failed to elaborate");
+    let addresses = |memory_map: &MemoryMap| -> Vec<(String, u64, Option<u64>)> {
+        memory_map
+            .flatten()
+            .into_iter()
+            .map(|field| (field.path, field.address, field.offset))
+            .collect()
+    };
+    let expected: Vec<_> = addresses(&sample_map())
+        .into_iter()
+        .map(|(path, address, offset)| (path, address + 0x4000, offset))
+        .collect();
+    assert_eq!(addresses(&memory_map), expected);
+    assert_eq!(memory_map.root().address(), Some(0x4000));
+    assert_eq!(memory_map.address_report().gaps, []);
+    assert!(
+        memory_map
+            .to_ipxact("relocated")
+            .contains("<ipxact:baseAddress>'h4000<")
+    );
+
+    // The relocated map must still fit below addressMax
+    let mut memory_map: MemoryMap =
+        serde_json::from_str(&relocated.replace("\"0xFFFF_FFFF\"", "\"0x4010\""))
+            .expect("Failed to parse JSON");
+    let err = memory_map
+        .elaborate()
+        .expect_err("Elaborated a map relocated past addressMax");
+    assert!(err.to_string().contains("0x4010"), "{err}");
+}
+
+#[test]
+pub fn packed_bits_renderers() {
+    let memory_map = elaborate(
+        r#"name = "Flags"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "ctrl"
+type = "set"
+packBits = true
+
+[[contains.contains]]
+name = "enable"
+type = "boolean"
+reset = true
+
+[[contains.contains]]
+name = "mode"
+type.unsigned = 3
+reset = 5
+
+[[contains.contains]]
+name = "count"
+type.unsigned = 8
+"#,
+    );
+    let header = memory_map.to_c_header("FLAGS_H");
+    assert!(header.contains(
+        "#define CTRL_MODE_ADDR 0x00u\n#define CTRL_MODE_SHIFT 1u\n#define CTRL_MODE_MASK 0x0Eu\n"
+    ));
+    assert!(header.contains(
+        "typedef struct {
+    uint8_t enable_mode;
+    uint8_t count;
+} flags_ctrl_t;
+"
+    ));
+    assert!(header.contains("    flags_ctrl_t ctrl;\n} flags_t;"));
+
+    let vhdl = memory_map.to_vhdl_package("flags_pkg");
+    assert!(vhdl_diagnostics(&vhdl).is_empty());
+    assert!(vhdl.contains("subtype CTRL_ENABLE_RANGE is natural range 0 downto 0;"));
+    assert!(vhdl.contains("subtype CTRL_MODE_RANGE is natural range 3 downto 1;"));
+    assert!(!vhdl.contains("CTRL_COUNT_RANGE"));
+
+    let xml = memory_map.to_ipxact("flags");
+    assert_eq!(xml.matches("<ipxact:register>").count(), 2);
+    assert!(xml.contains(
+        "<ipxact:name>ENABLE_MODE</ipxact:name>
+            <ipxact:displayName>enable, mode</ipxact:displayName>
+            <ipxact:addressOffset>'h0</ipxact:addressOffset>
+            <ipxact:size>8</ipxact:size>"
+    ));
+    assert!(xml.contains(
+        "<ipxact:name>MODE</ipxact:name>
+              <ipxact:bitOffset>1</ipxact:bitOffset>"
+    ));
+}
@@ -1,7 +1,9 @@
-use serde_json;
+use serde::Deserialize;
 use std::fs;
-use toml;
-use vhdl_doc::memory_map::schema::MemoryMap;
+use std::path::Path;
+use vhdl_doc::memory_map::loader::{InputFormat, load_memory_map};
+use vhdl_doc::memory_map::schema::{MemoryMap, get_memory_map_schema, lenient_hex_or_unsigned};
+use vhdl_doc::memory_map::template::MEMORY_MAP_TEMPLATE;
 
 #[test]
 pub fn toml_to_json() {
@@ -13,12 +15,672 @@ pub fn toml_to_json() {
     );
 }
 
+/// Parses `json`, serializes it to TOML and back, and checks that nothing changed
+fn assert_toml_round_trip(json: &str) {
+    let memory_map: MemoryMap = serde_json::from_str(json).expect("Failed to parse JSON");
+    let toml = toml::to_string_pretty(&memory_map).expect("Failed to serialize to TOML string");
+    let parsed: MemoryMap = toml::from_str(&toml).expect("Failed to parse TOML");
+    assert_eq!(
+        parsed.to_json().expect("Failed to serialize"),
+        memory_map.to_json().expect("Failed to serialize"),
+        "{toml}"
+    );
+}
+
 #[test]
 pub fn json_to_toml() {
     let contents = fs::read_to_string("tests/assets/memory_map.json").expect("Failed to read file");
-    let memory_map: MemoryMap = serde_json::from_str(&contents).expect("Failed to parse JSON");
-    println!(
-        "{}",
-        toml::to_string_pretty(&memory_map).expect("Failed to serialize to TOML string")
+    assert_toml_round_trip(&contents);
+    let mut memory_map: MemoryMap = serde_json::from_str(&contents).expect("Failed to parse JSON");
+    memory_map.elaborate().expect("Failed to elaborate");
+    assert_toml_round_trip(&memory_map.to_json().expect("Failed to serialize"));
+}
+
+#[test]
+pub fn toml_round_trip_every_type() {
+    let json = r#"{
+    "protocol": { "addressMax": "0xFFFF", "dataMin": 1 },
+    "name": "Types",
+    "type": "set",
+    "contains": [
+        { "name": "boolean", "type": "boolean", "value": true },
+        { "name": "string", "type": { "string": 4 }, "value": "abcd" },
+        {
+            "name": "enum",
+            "type": { "enum": { "length": 2, "map": { "idle": 0, "busy": 1 } } },
+            "value": "busy"
+        },
+        {
+            "name": "discrete bitfield",
+            "type": { "bitfield": { "length": 8, "bits": { "ready": 0, "error": 7 } } }
+        },
+        {
+            "name": "bitfield from zero",
+            "type": { "bitfield": { "length": 8, "bits": ["ready", "error"] } }
+        },
+        { "name": "unsigned", "type": { "unsigned": 16 }, "value": "0x10", "max": 1000 },
+        { "name": "signed", "type": { "signed": 8 }, "value": -3 },
+        { "name": "ufixed", "type": { "ufixed": { "high": 3, "low": -4 } }, "value": 1.25 },
+        { "name": "sfixed", "type": { "sfixed": { "high": 3, "low": -4 } }, "value": -1.5 },
+        {
+            "name": "array",
+            "type": { "array": { "count": 2, "stride": 4, "element": { "unsigned": 16 } } }
+        },
+        {
+            "name": "nested array",
+            "type": {
+                "array": {
+                    "count": 2,
+                    "element": { "array": { "count": 2, "element": { "ufixed": { "high": 3, "low": 0 } } } }
+                }
+            }
+        },
+        { "name": "reserved", "type": { "reserved": 4 } },
+        {
+            "name": "set",
+            "type": "set",
+            "contains": { "name": "last", "type": "boolean", "address": -1 }
+        }
+    ]
+}"#;
+    assert_toml_round_trip(json);
+    let mut memory_map: MemoryMap = serde_json::from_str(json).expect("Failed to parse JSON");
+    memory_map.elaborate().expect("Failed to elaborate");
+    assert_toml_round_trip(&memory_map.to_json().expect("Failed to serialize"));
+}
+
+#[test]
+pub fn registers_table_matches_contains() {
+    let protocol = "[protocol]\naddressMax = 0xFF\ndataMin = 1\n";
+    let registers = format!(
+        r#"name = "Flat"
+type = "set"
+registers = [
+    {{ name = "ctrl", address = 0x00, access = "rw", type.unsigned = 8 }},
+    {{ name = "stat", address = 0x01, access = "r", type.unsigned = 8 }},
+]
+{protocol}"#
+    );
+    let contains = format!(
+        r#"name = "Flat"
+type = "set"
+{protocol}
+[[contains]]
+name = "ctrl"
+address = 0x00
+access = "rw"
+type.unsigned = 8
+
+[[contains]]
+name = "stat"
+address = 0x01
+access = "r"
+type.unsigned = 8
+"#
+    );
+    let from_registers: MemoryMap = toml::from_str(&registers).expect("Failed to parse TOML");
+    let from_contains: MemoryMap = toml::from_str(&contains).expect("Failed to parse TOML");
+    assert_eq!(
+        serde_json::to_value(&from_registers).unwrap(),
+        serde_json::to_value(&from_contains).unwrap()
+    );
+}
+
+#[test]
+pub fn registers_table_excludes_contains() {
+    let contents = r#"name = "Flat"
+type = "set"
+registers = [{ name = "ctrl", type.unsigned = 8 }]
+contains = { name = "stat", type.unsigned = 8 }
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#;
+    let err = toml::from_str::<MemoryMap>(contents)
+        .err()
+        .expect("Parsed conflicting map");
+    assert!(err.to_string().contains("mutually exclusive"));
+}
+
+#[test]
+pub fn template_round_trip() {
+    let mut memory_map: MemoryMap =
+        toml::from_str(MEMORY_MAP_TEMPLATE).expect("Failed to parse template");
+    memory_map
+        .elaborate()
+        .expect("Failed to elaborate template");
+    let json = serde_json::to_string(&memory_map).expect("Failed to serialize to JSON string");
+    let mut parsed: MemoryMap = serde_json::from_str(&json).expect("Failed to parse JSON");
+    parsed
+        .elaborate()
+        .expect("Failed to elaborate the elaborated template");
+    assert_eq!(
+        serde_json::to_string(&parsed).expect("Failed to serialize to JSON string"),
+        json
+    );
+}
+
+#[test]
+pub fn hex_value_full_range() {
+    let contents = r#"name = "Max"
+type.unsigned = 64
+value = "0xFFFF_FFFF_FFFF_FFFF"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#;
+    let mut memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    memory_map.elaborate().expect("Failed to elaborate");
+    let json = serde_json::to_value(&memory_map).expect("Failed to serialize to JSON value");
+    assert_eq!(json["value"], "0xFFFF_FFFF_FFFF_FFFF");
+}
+
+#[test]
+pub fn hex_value_out_of_range() {
+    let contents = r#"name = "Max"
+type.unsigned = 64
+value = "0x1_0000_0000_0000_0000"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#;
+    let err = toml::from_str::<MemoryMap>(contents)
+        .err()
+        .expect("Parsed out of range value");
+    assert!(
+        err.to_string()
+            .contains("exceeds the maximum representable value")
+    );
+}
+
+#[test]
+pub fn schema_documents_array() {
+    let schema: serde_json::Value =
+        serde_json::from_str(&get_memory_map_schema()).expect("Failed to parse schema");
+    let field_type = &schema["$defs"]["FieldType"]["oneOf"];
+    assert!(
+        field_type
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|variant| variant["properties"]["array"]["properties"]["stride"].is_object())
+    );
+}
+
+#[test]
+pub fn description_round_trip() {
+    let contents = r#"name = "Described"
+type.unsigned = 8
+description = """
+First line.
+Second line."""
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#;
+    let memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    let json = serde_json::to_string(&memory_map).expect("Failed to serialize to JSON string");
+    let memory_map: MemoryMap = serde_json::from_str(&json).expect("Failed to parse JSON");
+    let toml = toml::to_string(&memory_map).expect("Failed to serialize to TOML string");
+    let memory_map: MemoryMap = toml::from_str(&toml).expect("Failed to parse TOML");
+    let json = serde_json::to_value(&memory_map).expect("Failed to serialize to JSON value");
+    assert_eq!(json["description"], "First line.\nSecond line.");
+}
+
+#[test]
+pub fn boolean_round_trip() {
+    let contents = r#"name = "Enable"
+type = "boolean"
+value = true
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#;
+    let memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    let json = serde_json::to_string(&memory_map).expect("Failed to serialize to JSON string");
+    let memory_map: MemoryMap = serde_json::from_str(&json).expect("Failed to parse JSON");
+    let toml = toml::to_string(&memory_map).expect("Failed to serialize to TOML string");
+    let memory_map: MemoryMap = toml::from_str(&toml).expect("Failed to parse TOML");
+    let json = serde_json::to_value(&memory_map).expect("Failed to serialize to JSON value");
+    assert_eq!(json["type"], "boolean");
+    assert_eq!(json["value"], true);
+}
+
+fn address_max(literal: &str) -> Result<u64, toml::de::Error> {
+    let contents = format!(
+        r#"name = "Literal"
+type.unsigned = 8
+
+[protocol]
+addressMax = {literal}
+dataMin = 1
+"#
+    );
+    let memory_map: MemoryMap = toml::from_str(&contents)?;
+    let json = serde_json::to_value(&memory_map).expect("Failed to serialize to JSON value");
+    Ok(json["protocol"]["addressMax"].as_u64().unwrap())
+}
+
+#[test]
+pub fn binary_literal() {
+    assert_eq!(address_max(r#""0b1010_0000""#).unwrap(), 160);
+}
+
+#[test]
+pub fn octal_literal() {
+    assert_eq!(address_max(r#""0o7_77""#).unwrap(), 511);
+}
+
+#[test]
+pub fn invalid_binary_literal() {
+    let err = address_max(r#""0b2""#).expect_err("Parsed invalid binary string");
+    assert!(
+        err.to_string()
+            .contains("failed to parse binary string 0b2")
+    );
+}
+
+#[test]
+pub fn uppercase_hex_prefix() {
+    assert_eq!(address_max(r#""0XFF""#).unwrap(), 255);
+}
+
+#[test]
+pub fn bare_hex_strict() {
+    let err = address_max(r#""FF""#).expect_err("Parsed bare hex string");
+    assert!(
+        err.to_string()
+            .contains("expected a '0x', '0b' or '0o' prefixed string")
+    );
+}
+
+#[test]
+pub fn bare_hex_lenient() {
+    #[derive(Deserialize)]
+    struct Lenient {
+        #[serde(deserialize_with = "lenient_hex_or_unsigned")]
+        mask: u64,
+    }
+    let lenient: Lenient = toml::from_str(r#"mask = "FF""#).expect("Failed to parse TOML");
+    assert_eq!(lenient.mask, 255);
+    let lenient: Lenient = toml::from_str(r#"mask = "0XFF""#).expect("Failed to parse TOML");
+    assert_eq!(lenient.mask, 255);
+}
+
+#[test]
+pub fn yaml_matches_json() {
+    let yaml = load_memory_map(Path::new("tests/assets/memory_map.yaml"), None, false)
+        .expect("Failed to load YAML");
+    let json = load_memory_map(Path::new("tests/assets/memory_map.json"), None, false)
+        .expect("Failed to load JSON");
+    let yaml = serde_json::to_value(&yaml).expect("Failed to serialize to JSON value");
+    assert_eq!(yaml, serde_json::to_value(&json).unwrap());
+    assert_eq!(yaml["protocol"]["addressMax"], 0xFFFF_FFFFu64);
+    assert_eq!(yaml["contains"]["address"], "0x0000");
+    let yaml = serde_yaml::to_string(&yaml).expect("Failed to serialize to YAML string");
+    let memory_map = InputFormat::Yaml
+        .parse(&yaml)
+        .expect("Failed to parse round-tripped YAML");
+    assert_eq!(
+        serde_json::to_value(&memory_map).unwrap(),
+        serde_json::to_value(&json).unwrap()
+    );
+}
+
+#[test]
+pub fn side_effect_access_round_trip() {
+    let contents = r#"name = "Status"
+type = "set"
+access = "w1c"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "irq"
+type.bitfield = { length = 2, bits = ["rx", "tx"] }
+
+[[contains]]
+name = "start"
+access = "strobe"
+type = "boolean"
+"#;
+    let mut memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    let toml = toml::to_string(&memory_map).expect("Failed to serialize to TOML string");
+    let _: MemoryMap = toml::from_str(&toml).expect("Failed to parse TOML");
+    memory_map.elaborate().expect("Failed to elaborate");
+    let json = serde_json::to_string(&memory_map).expect("Failed to serialize to JSON string");
+    let memory_map: MemoryMap = serde_json::from_str(&json).expect("Failed to parse JSON");
+    let json = serde_json::to_value(&memory_map).expect("Failed to serialize to JSON value");
+    assert_eq!(json["access"], "w1c");
+    assert_eq!(json["contains"][0]["access"], "w1c");
+    assert_eq!(json["contains"][1]["access"], "strobe");
+    let schema = get_memory_map_schema();
+    assert!(schema.contains(r#""const": "w1c""#));
+    assert!(schema.contains(r#""const": "strobe""#));
+}
+
+#[test]
+pub fn schema_metadata() {
+    let contents = get_memory_map_schema();
+    let schema: serde_json::Value =
+        serde_json::from_str(&contents).expect("Failed to parse schema");
+    assert_eq!(
+        schema["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    assert_eq!(schema["title"], "VHDL Memory Map");
+    assert!(schema["description"].is_string());
+    assert!(contents.starts_with("{\n    \"$schema\""));
+}
+
+#[test]
+pub fn assets_match_schema() {
+    for path in [
+        "tests/assets/memory_map.toml",
+        "tests/assets/memory_map.json",
+        "tests/assets/memory_map.yaml",
+    ] {
+        load_memory_map(Path::new(path), None, true).expect("Failed to validate asset");
+    }
+    InputFormat::Toml
+        .parse_validated(MEMORY_MAP_TEMPLATE)
+        .expect("Failed to validate template");
+}
+
+#[test]
+pub fn schema_matches_prefixed_int_parsing() {
+    let contents = |address: &str| {
+        format!(
+            r#"name = "Prefixed"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "ctrl"
+address = "{address}"
+type.unsigned = 8
+"#
+        )
+    };
+    for address in ["0x1F", "0X1F", "0b101", "0o17"] {
+        InputFormat::Toml
+            .parse_validated(&contents(address))
+            .unwrap_or_else(|err| panic!("Failed to validate {address}: {err}"));
+    }
+    for address in ["0B101", "0O17", "0b2F", "0o9"] {
+        let err = InputFormat::Toml
+            .parse_validated(&contents(address))
+            .err()
+            .unwrap_or_else(|| panic!("Validated {address}"));
+        assert!(err.to_string().contains("/contains/0/address"), "{err}");
+    }
+}
+
+#[test]
+pub fn schema_rejects_unknown_key() {
+    let contents = r#"name = "Typo"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "ctrl"
+adress = 0x10
+type.unsigned = 8
+"#;
+    let err = InputFormat::Toml
+        .parse_validated(contents)
+        .err()
+        .expect("Validated map with an unknown key");
+    let message = err.to_string();
+    assert!(message.contains("/contains/0"), "{message}");
+    assert!(message.contains("'adress' was unexpected"), "{message}");
+}
+
+#[test]
+pub fn misspelled_address_rejected() {
+    let nested = r#"name = "Typo"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "ctrl"
+adress = 0x10
+type.unsigned = 8
+"#;
+    let err = toml::from_str::<MemoryMap>(nested)
+        .err()
+        .expect("Parsed misspelled nested address");
+    assert!(err.to_string().contains("unknown field `adress`"), "{err}");
+    let top_level = r#"name = "Typo"
+adress = 0x10
+type.unsigned = 8
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#;
+    let err = toml::from_str::<MemoryMap>(top_level)
+        .err()
+        .expect("Parsed misspelled top-level address");
+    assert!(err.to_string().contains("unknown field `adress`"), "{err}");
+    let protocol = r#"{
+    "name": "Typo",
+    "type": { "unsigned": 8 },
+    "protocol": { "adressMax": 255, "dataMin": 1 }
+}"#;
+    let err = serde_json::from_str::<MemoryMap>(protocol)
+        .err()
+        .expect("Parsed misspelled protocol key");
+    assert!(
+        err.to_string().contains("unknown field `adressMax`"),
+        "{err}"
+    );
+}
+
+#[test]
+pub fn hex_reset_round_trip() {
+    let contents = r#"name = "Magic"
+type.unsigned = 32
+reset = "0xDEAD_BEEF"
+value = 16
+
+[protocol]
+addressMax = 0xFF
+dataMin = 4
+"#;
+    let mut memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    memory_map.elaborate().expect("Failed to elaborate");
+    let toml = toml::to_string(&memory_map).expect("Failed to serialize to TOML string");
+    assert!(toml.contains(r#"reset = "0xDEAD_BEEF""#), "{toml}");
+    let memory_map: MemoryMap = toml::from_str(&toml).expect("Failed to parse TOML");
+    let json = serde_json::to_string(&memory_map).expect("Failed to serialize to JSON string");
+    let mut memory_map: MemoryMap = serde_json::from_str(&json).expect("Failed to parse JSON");
+    memory_map.elaborate().expect("Failed to elaborate");
+    let json = serde_json::to_value(&memory_map).expect("Failed to serialize to JSON value");
+    assert_eq!(json["reset"], "0xDEAD_BEEF");
+    assert_eq!(json["value"], 16);
+}
+
+#[test]
+pub fn negative_hex_value() {
+    let contents = r#"name = "Offset"
+type.signed = 8
+value = "-0x80"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#;
+    let mut memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    memory_map.elaborate().expect("Failed to elaborate");
+    let json = serde_json::to_value(&memory_map).expect("Failed to serialize to JSON value");
+    assert_eq!(json["value"], "-0x80");
+    assert_eq!(json["range"], "-128 .. 127");
+}
+
+#[test]
+pub fn enum_serialization_deterministic() {
+    let contents = r#"name = "Mode"
+type.enum.length = 3
+type.enum.map = { run = 1, idle = 0, halt = 3, sleep = 2, reset = 4 }
+value = "idle"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#;
+    let serialize = || {
+        let memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+        serde_json::to_string(&memory_map).expect("Failed to serialize to JSON string")
+    };
+    let json = serialize();
+    assert_eq!(serialize(), json);
+    assert!(json.contains(r#""map":{"halt":3,"idle":0,"reset":4,"run":1,"sleep":2}"#));
+}
+
+/// Memory map holding a single enum with the given map
+fn enum_map(map: &str) -> Result<MemoryMap, toml::de::Error> {
+    toml::from_str(&format!(
+        r#"name = "Mode"
+type.enum.length = 2
+type.enum.map = {map}
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+"#
+    ))
+}
+
+#[test]
+pub fn enum_map_keyed_by_index() {
+    let by_index = enum_map(r#"{ 0 = "idle", 1 = "run", 0x3 = "halt" }"#)
+        .expect("Failed to parse index keyed map");
+    let by_name =
+        enum_map("{ idle = 0, run = 1, halt = 3 }").expect("Failed to parse name keyed map");
+    let json = serde_json::to_string(&by_index).expect("Failed to serialize to JSON string");
+    assert_eq!(
+        json,
+        serde_json::to_string(&by_name).expect("Failed to serialize to JSON string")
+    );
+    assert!(json.contains(r#""map":{"halt":3,"idle":0,"run":1}"#));
+    let memory_map: MemoryMap = serde_json::from_str(&json).expect("Failed to parse JSON");
+    let toml = toml::to_string(&memory_map).expect("Failed to serialize to TOML string");
+    let memory_map: MemoryMap = toml::from_str(&toml).expect("Failed to parse TOML");
+    assert_eq!(
+        serde_json::to_string(&memory_map).expect("Failed to serialize to JSON string"),
+        json
+    );
+
+    let yaml = "name: Mode\ntype:\n  enum:\n    length: 2\n    map: {0: idle, 1: run}\n\
+                protocol:\n  addressMax: 0xFF\n  dataMin: 1\n";
+    let memory_map: MemoryMap = serde_yaml::from_str(yaml).expect("Failed to parse YAML");
+    let json = serde_json::to_value(&memory_map).expect("Failed to serialize to JSON value");
+    assert_eq!(json["type"]["enum"]["map"]["run"], 1);
+
+    for (map, message) in [
+        (
+            r#"{ 1 = "idle", 0x1 = "run" }"#,
+            "lists index 1 more than once",
+        ),
+        (
+            r#"{ 0 = "idle", 1 = "idle" }"#,
+            "gives variant idle more than one index",
+        ),
+        (
+            r#"{ 0 = "idle", run = 1 }"#,
+            "mixes name = index and index = name",
+        ),
+        (r#"{ zero = "idle" }"#, "key zero is not an index"),
+    ] {
+        let err = enum_map(map).err().expect("Invalid enum map was accepted");
+        assert!(err.to_string().contains(message), "{err}");
+    }
+}
+
+#[test]
+pub fn address_max_size_suffix() {
+    let contents = r#"name = "Sized"
+type.unsigned = 8
+
+[protocol]
+addressMax = "64K"
+dataMin = 1
+"#;
+    let parse = |address_max: &str| {
+        toml::from_str::<MemoryMap>(&contents.replace("64K", address_max))
+            .map(|memory_map| memory_map.protocol().address_max())
+    };
+    assert_eq!(parse("64K").expect("Failed to parse 64K"), 65536);
+    assert_eq!(parse("1M").expect("Failed to parse 1M"), 1 << 20);
+    assert_eq!(parse("2G").expect("Failed to parse 2G"), 2 << 30);
+    let err = parse("0x1K").expect_err("Parsed a hex size");
+    assert!(
+        err.to_string()
+            .contains("a K, M or G suffix must follow decimal digits"),
+        "{err}"
     );
+    for invalid in ["64T", "K"] {
+        let err = parse(invalid).expect_err("Parsed an invalid size");
+        assert!(
+            err.to_string().contains("failed to parse"),
+            "{invalid}: {err}"
+        );
+    }
+    let err = toml::from_str::<MemoryMap>(&contents.replace("\"64K\"", "-1"))
+        .err()
+        .expect("Parsed a negative size");
+    assert!(
+        err.to_string().contains("invalid value: integer `-1`"),
+        "{err}"
+    );
+}
+
+#[test]
+pub fn hex_address_round_trip() {
+    let contents = r#"name = "Device"
+type = "set"
+
+[protocol]
+addressMax = 0xFF
+dataMin = 1
+
+[[contains]]
+name = "control"
+type.unsigned = 8
+address = "0x10"
+
+[[contains]]
+name = "status"
+type.unsigned = 8
+address = 0x20
+"#;
+    let mut memory_map: MemoryMap = toml::from_str(contents).expect("Failed to parse TOML");
+    let json = serde_json::to_value(&memory_map).expect("Failed to serialize to JSON value");
+    assert_eq!(json["contains"][0]["address"], "0x10");
+    assert_eq!(json["contains"][1]["address"], 0x20);
+    let toml = toml::to_string(&memory_map).expect("Failed to serialize to TOML string");
+    assert!(toml.contains(r#"address = "0x10""#), "{toml}");
+
+    memory_map.elaborate().expect("Failed to elaborate");
+    let json = serde_json::to_value(&memory_map).expect("Failed to serialize to JSON value");
+    assert_eq!(json["address"], 0);
+    assert_eq!(json["contains"][0]["address"], "0x10");
+    assert_eq!(json["contains"][1]["address"], 0x20);
 }
@@ -0,0 +1,72 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use vhdl_doc::symbol::symbol::{DEFAULT_SYMBOL_COLOR, make_symbol, make_symbol_svg, parse_color};
+
+/// Fresh output directory for a single test
+fn doc_path(test: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("vhdl_doc_symbol_{test}"));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+    path
+}
+
+#[test]
+pub fn symbol_64() {
+    let doc_path = doc_path("symbol_64");
+    make_symbol(doc_path.clone(), 64, DEFAULT_SYMBOL_COLOR).expect("Failed to make symbol");
+    let img = image::open(doc_path.join("test.png"))
+        .expect("Failed to read symbol")
+        .to_rgb8();
+    assert_eq!(img.dimensions(), (64, 64));
+    for (x, y) in [(31, 31), (32, 32), (32, 16), (16, 32), (47, 32)] {
+        assert_eq!(img.get_pixel(x, y).0, [255, 0, 0], "pixel ({x}, {y})");
+    }
+    assert_eq!(img.get_pixel(0, 0).0, [0, 0, 0]);
+    assert_eq!(img.get_pixel(32, 15).0, [0, 0, 0]);
+}
+
+#[test]
+pub fn symbol_too_small() {
+    let doc_path = doc_path("symbol_too_small");
+    let err = make_symbol(doc_path.clone(), 4, DEFAULT_SYMBOL_COLOR)
+        .expect_err("Made a degenerate symbol");
+    assert!(
+        err.to_string()
+            .contains("smaller than the minimum of 8 pixels")
+    );
+    assert!(!doc_path.join("test.png").exists());
+}
+
+#[test]
+pub fn symbol_svg() {
+    let doc_path = doc_path("symbol_svg");
+    make_symbol_svg(doc_path.clone(), DEFAULT_SYMBOL_COLOR).expect("Failed to make SVG symbol");
+    let svg = fs::read_to_string(doc_path.join("test.svg")).expect("Failed to read symbol");
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 32 32\">"));
+    assert!(svg.contains("<rect x=\"15\" y=\"8\" width=\"3\" height=\"16\" fill=\"#ff0000\"/>"));
+    assert!(svg.contains("<rect x=\"8\" y=\"15\" width=\"16\" height=\"3\" fill=\"#ff0000\"/>"));
+}
+
+#[test]
+pub fn symbol_green() {
+    let doc_path = doc_path("symbol_green");
+    let green = parse_color("#00FF00").expect("Failed to parse color");
+    make_symbol(doc_path.clone(), 32, green).expect("Failed to make symbol");
+    let img = image::open(doc_path.join("test.png"))
+        .expect("Failed to read symbol")
+        .to_rgb8();
+    assert_eq!(img.get_pixel(16, 16).0, [0, 255, 0]);
+}
+
+#[test]
+pub fn invalid_colors() {
+    for color in ["00FF00", "#00FF0", "#00FF00F", "#GG0000", "#ééé"] {
+        let err = parse_color(color).expect_err("Parsed an invalid color");
+        assert!(
+            err.to_string().contains("expected a #RRGGBB hex string"),
+            "{color}"
+        );
+    }
+    assert_eq!(parse_color("#1a2B3c").unwrap(), [0x1a, 0x2b, 0x3c]);
+}
@@ -0,0 +1,106 @@
+use vhdl_doc::memory_map::schema::{Access, MemoryMap};
+
+const BLOCK_RDL: &str = r#"
+// Registers of a small peripheral
+reg status_t {
+    desc = "Interrupt status";
+    field { sw = rw; hw = w; onwrite = woclr; } done[0:0] = 0;
+    field { sw = rw; hw = w; onwrite = woclr; } error[3:3] = 1;
+};
+
+addrmap block {
+    name = "Example block";
+    reg {
+        desc = "Control register";
+        field { sw = rw; hw = r; } enable[0:0] = 1;
+    } ctrl @ 0x0;
+    status_t status @ 0x4;
+    reg {
+        field { sw = r; hw = w; desc = "Low byte"; } low[7:0] = 8'h12;
+        field { sw = r; hw = w; } high[15:8];
+    } data;
+    regfile {
+        reg { regwidth = 16; field { sw = w; singlepulse; } start[15:0]; } go @ 0x2;
+    } ctl @ 0x10;
+};
+"#;
+
+#[test]
+pub fn systemrdl_round_trip() {
+    let imported = MemoryMap::from_systemrdl(BLOCK_RDL).expect("Failed to import SystemRDL");
+    let json = imported.to_json().expect("Failed to serialize");
+    let mut memory_map: MemoryMap = serde_json::from_str(&json).expect("Failed to parse JSON");
+    assert_eq!(memory_map.to_json().expect("Failed to serialize"), json);
+    memory_map.elaborate().expect("Failed to elaborate");
+    let fields: Vec<_> = memory_map
+        .flatten()
+        .into_iter()
+        .map(|field| (field.path, field.address, field.access))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("ctrl".to_string(), 0x0, Access::ReadWrite),
+            ("status".to_string(), 0x4, Access::W1C),
+            ("data.low".to_string(), 0x8, Access::Read),
+            ("data.high".to_string(), 0x9, Access::Read),
+            ("ctl.go".to_string(), 0x12, Access::Strobe),
+        ]
+    );
+    let value: serde_json::Value = serde_json::from_str(&json).expect("Failed to parse JSON");
+    assert_eq!(value["name"], "block");
+    assert_eq!(value["protocol"]["addressMax"], 0x1F);
+    let registers = &value["contains"];
+    assert_eq!(registers[0]["type"], "boolean");
+    assert_eq!(registers[0]["reset"], true);
+    assert_eq!(registers[1]["type"]["bitfield"]["bits"]["error"], 3);
+    assert_eq!(registers[1]["reset"], 0x8);
+    assert_eq!(registers[2]["contains"][0]["reset"], 0x12);
+}
+
+#[test]
+pub fn systemrdl_rejects_unsupported_constructs() {
+    let cases = [
+        (
+            "addrmap a { enum mode_e { A = 0; }; };",
+            "Unsupported SystemRDL construct `enum` at line 1",
+        ),
+        (
+            "addrmap a { reg { field { rclr; } f[0:0]; } r; };",
+            "Unsupported property `rclr` of field f at line 1",
+        ),
+        (
+            "addrmap a { reg { field {} f[0:0]; } r[4] @ 0; };",
+            "Arrays of reg such as r at line 1 are not supported",
+        ),
+        (
+            "addrmap a {\n reg { field {} f[0:0]; field {} g[4:1]; } r;\n};",
+            "Register r at line 2 mixes single-bit fields",
+        ),
+        (
+            "addrmap a { reg { field {} f[18446744073709551615:0]; } r; };",
+            "Field f at line 1 does not fit in the 32-bit register r",
+        ),
+        (
+            "addrmap a { reg { regwidth = 9223372036854775808; field {} f; } r; };",
+            "Register r at line 1 has regwidth 9223372036854775808",
+        ),
+        (
+            "addrmap a { reg { field {} f; } r @ 0xFFFFFFFFFFFFFFFF; };",
+            "reg r at line 1 placed at 0xffffffffffffffff overflows the address space",
+        ),
+        (
+            "addrmap a {\n regfile { reg { field {} f; } r @ 0x10; } b @ 0xFFFFFFFFFFFFFFFF;\n};",
+            "reg r at line 2 placed at 0xffffffffffffffff overflows the address space",
+        ),
+    ];
+    for (rdl, expected) in cases {
+        let err = MemoryMap::from_systemrdl(rdl)
+            .err()
+            .expect("Unsupported SystemRDL was imported");
+        assert!(
+            err.to_string().starts_with(expected),
+            "{err} does not start with {expected}"
+        );
+    }
+}